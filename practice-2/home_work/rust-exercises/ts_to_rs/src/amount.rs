@@ -0,0 +1,143 @@
+use anyhow::{Result, anyhow, bail};
+use std::{fmt, str::FromStr};
+
+/// SOL amounts are expressed in lamports, 10^-9 SOL.
+pub const SOL_DECIMALS: u8 = 9;
+
+/// A user-facing decimal amount such as `12.5`, kept as text until the
+/// number of decimals is known so it can be converted without rounding.
+#[derive(Clone, Debug)]
+pub struct UiAmount {
+    whole: String,
+    fraction: String,
+}
+
+impl FromStr for UiAmount {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+        let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+        if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+            bail!("'{}' is not a valid amount, expected e.g. 12.5", value);
+        }
+        Ok(UiAmount {
+            whole: whole.to_string(),
+            fraction: fraction.trim_end_matches('0').to_string(),
+        })
+    }
+}
+
+impl fmt::Display for UiAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let whole = if self.whole.is_empty() {
+            "0"
+        } else {
+            &self.whole
+        };
+        if self.fraction.is_empty() {
+            write!(f, "{}", whole)
+        } else {
+            write!(f, "{}.{}", whole, self.fraction)
+        }
+    }
+}
+
+impl UiAmount {
+    /// Converts to base units, e.g. `12.5` with 2 decimals is `1250`.
+    pub fn to_raw(&self, decimals: u8) -> Result<u64> {
+        if self.fraction.len() > decimals as usize {
+            bail!("Amount {} has more than {} decimal places", self, decimals);
+        }
+        let digits = format!(
+            "{}{:0<width$}",
+            self.whole,
+            self.fraction,
+            width = decimals as usize
+        );
+        let raw = if digits.is_empty() {
+            0
+        } else {
+            digits
+                .parse::<u64>()
+                .map_err(|_| anyhow!("Amount {} is too large", self))?
+        };
+        if raw == 0 {
+            bail!("Amount must be greater than zero");
+        }
+        Ok(raw)
+    }
+}
+
+/// Formats base units back into a decimal amount, e.g. `1250` with 2
+/// decimals is `12.5`.
+pub fn format_amount(raw: u64, decimals: u8) -> String {
+    let digits = format!("{:0>width$}", raw, width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(amount: &str, decimals: u8) -> Result<u64> {
+        amount.parse::<UiAmount>()?.to_raw(decimals)
+    }
+
+    #[test]
+    fn converts_decimal_amounts() {
+        assert_eq!(raw("12.5", 2).unwrap(), 1250);
+        assert_eq!(raw("12.5", SOL_DECIMALS).unwrap(), 12_500_000_000);
+        assert_eq!(raw(".5", 2).unwrap(), 50);
+        assert_eq!(raw("12.", 2).unwrap(), 1200);
+        assert_eq!(raw("12", 0).unwrap(), 12);
+    }
+
+    #[test]
+    fn ignores_trailing_zeros() {
+        assert_eq!(raw("1.500000", 2).unwrap(), 150);
+        assert_eq!(raw("3.000", 0).unwrap(), 3);
+        assert_eq!("1.2500".parse::<UiAmount>().unwrap().to_string(), "1.25");
+    }
+
+    #[test]
+    fn rejects_too_many_decimals() {
+        assert!(raw("1.234", 2).is_err());
+        assert!(raw("0.5", 0).is_err());
+    }
+
+    #[test]
+    fn rejects_amounts_overflowing_u64() {
+        assert_eq!(raw("18446744073709551615", 0).unwrap(), u64::MAX);
+        assert!(raw("18446744073709551616", 0).is_err());
+        assert!(raw("18446744073.709551616", 9).is_err());
+    }
+
+    #[test]
+    fn rejects_zero_and_malformed_amounts() {
+        assert!(raw("0", 2).is_err());
+        assert!(raw("0.00", 2).is_err());
+        assert!(raw(".", 2).is_err());
+        assert!(raw("", 2).is_err());
+        assert!(raw("-1", 2).is_err());
+        assert!(raw("1.2.3", 2).is_err());
+        assert!(raw("1e3", 2).is_err());
+    }
+
+    #[test]
+    fn round_trips_through_format_amount() {
+        for (amount, decimals) in [("12.5", 2), ("0.000000001", 9), ("7", 0), ("100", 6)] {
+            let raw = raw(amount, decimals).unwrap();
+            assert_eq!(format_amount(raw, decimals), amount);
+        }
+        assert_eq!(format_amount(0, 2), "0");
+        assert_eq!(format_amount(u64::MAX, 9), "18446744073.709551615");
+    }
+}
@@ -0,0 +1,349 @@
+use clap::{Args, Parser, Subcommand};
+use solana_sdk::{hash::Hash, pubkey::Pubkey};
+use std::{path::PathBuf, str::FromStr};
+
+use crate::{amount::UiAmount, output::OutputFormat};
+
+#[derive(Parser)]
+#[command(name = "ts_to_rs", about = "Solana SOL and SPL token helpers")]
+pub struct Cli {
+    /// How to print the results
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+
+    /// RPC URL or cluster moniker (mainnet-beta, devnet, testnet, localhost).
+    /// Falls back to SOLANA_RPC_URL, then the Solana CLI config
+    #[arg(long, short = 'u', global = true)]
+    pub url: Option<String>,
+
+    /// processed, confirmed or finalized. Falls back to SOLANA_COMMITMENT,
+    /// then the Solana CLI config
+    #[arg(long, global = true)]
+    pub commitment: Option<String>,
+
+    /// Fee payer: a keypair JSON file, a base58 secret, a JSON byte array or
+    /// a BIP39 seed phrase. Falls back to SECRET_KEY, then the Solana CLI
+    /// config keypair, then ~/.config/solana/id.json
+    #[arg(long, short = 'k', global = true)]
+    pub keypair: Option<String>,
+
+    /// Derivation path used when the signer is a seed phrase
+    #[arg(long, global = true, default_value = "m/44'/501'/0'/0'")]
+    pub derivation_path: String,
+
+    /// Solana CLI config file, defaults to ~/.config/solana/cli/config.yml
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub tx: TxArgs,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Native SOL operations
+    #[command(subcommand)]
+    Sol(SolCommand),
+    /// SPL token operations
+    #[command(subcommand)]
+    Token(TokenCommand),
+    /// Offline signed transactions
+    #[command(subcommand)]
+    Tx(TxCommand),
+}
+
+/// How transactions are built, signed and whether they are sent.
+#[derive(Args)]
+pub struct TxArgs {
+    /// Sign without sending and print the transaction as base64. Needs
+    /// --blockhash, or --nonce to read the nonce value from the cluster
+    #[arg(long, global = true)]
+    pub sign_only: bool,
+    /// Recent blockhash, or durable nonce value, to sign with instead of
+    /// fetching one
+    #[arg(long, global = true)]
+    pub blockhash: Option<Hash>,
+    /// Durable nonce account, advanced as the first instruction
+    #[arg(long, global = true)]
+    pub nonce: Option<Pubkey>,
+    /// Nonce authority signer, defaults to the fee payer
+    #[arg(long, global = true, requires = "nonce")]
+    pub nonce_authority: Option<String>,
+    /// Additional signer (keypair file, base58, JSON or seed phrase), may be
+    /// repeated
+    #[arg(long = "signer", global = true)]
+    pub signers: Vec<String>,
+    /// Compute unit limit, instead of the runtime default
+    #[arg(long, global = true, conflicts_with = "simulate")]
+    pub compute_unit_limit: Option<u32>,
+    /// Simulate first and set the compute unit limit from the units consumed
+    #[arg(long, global = true, conflicts_with = "sign_only")]
+    pub simulate: bool,
+    /// Extra compute units on top of the simulated amount, in percent
+    #[arg(long, global = true, default_value_t = 20)]
+    pub compute_unit_margin: u32,
+    /// Priority fee in micro-lamports per compute unit
+    #[arg(long, global = true, conflicts_with = "auto_priority_fee")]
+    pub priority_fee: Option<u64>,
+    /// Priority fee from the median of recent prioritization fees paid for
+    /// the accounts the transaction writes to
+    #[arg(long, global = true, conflicts_with = "sign_only")]
+    pub auto_priority_fee: bool,
+}
+
+#[derive(Subcommand)]
+pub enum TxCommand {
+    /// Add signatures to a base64 transaction produced with --sign-only
+    Sign(TransactionArgs),
+    /// Submit a fully signed base64 transaction
+    Send(TransactionArgs),
+}
+
+#[derive(Args)]
+pub struct TransactionArgs {
+    /// Base64 encoded transaction
+    pub transaction: String,
+}
+
+#[derive(Subcommand)]
+pub enum SolCommand {
+    /// Transfer SOL to one or more recipients
+    Send(SendSolArgs),
+}
+
+#[derive(Subcommand)]
+pub enum TokenCommand {
+    /// Create a new token mint with the payer as mint authority
+    CreateMint(CreateMintArgs),
+    /// Mint tokens to a token account
+    Mint(MintArgs),
+    /// Create the associated token account of an owner
+    CreateAta(CreateAtaArgs),
+    /// Send tokens to every recipient in a CSV file
+    Distribute(DistributeArgs),
+    /// Token metadata operations
+    #[command(subcommand)]
+    Metadata(MetadataCommand),
+}
+
+#[derive(Args)]
+pub struct DistributeArgs {
+    #[arg(long)]
+    pub mint: Pubkey,
+    /// CSV file with `address,amount` rows, amounts in tokens
+    #[arg(long)]
+    pub recipients: PathBuf,
+    /// Where to record the outcome of each row. Rows already marked as sent
+    /// are skipped, so an interrupted run can be resumed. Defaults to
+    /// `<recipients>.results.csv`
+    #[arg(long)]
+    pub results: Option<PathBuf>,
+    /// Mint new tokens instead of transferring from the payer's account
+    #[arg(long)]
+    pub mint_new_tokens: bool,
+    /// The mint belongs to the Token-2022 program
+    #[arg(long)]
+    pub token_2022: bool,
+    /// Mint decimals, skips fetching the mint
+    #[arg(long)]
+    pub decimals: Option<u8>,
+    /// How often to retry a failed transaction
+    #[arg(long, default_value_t = 3)]
+    pub max_retries: u32,
+}
+
+#[derive(Subcommand)]
+pub enum MetadataCommand {
+    /// Create the Metaplex metadata account of a mint
+    Create(CreateMetadataArgs),
+    /// Fetch and decode the metadata account of a mint
+    Show(ShowMetadataArgs),
+    /// Update the metadata of a mint, the payer must be the update authority
+    Update(UpdateMetadataArgs),
+}
+
+#[derive(Args)]
+pub struct SendSolArgs {
+    /// Recipient address, may be repeated to send several transfers in one
+    /// transaction
+    #[arg(long = "to", required = true)]
+    pub recipients: Vec<Pubkey>,
+    /// Amount in SOL, e.g. 0.01. Given once for every recipient, or once per
+    /// recipient in the same order
+    #[arg(long = "amount", required = true)]
+    pub amounts: Vec<UiAmount>,
+    /// Memo attached to the transfer
+    #[arg(long)]
+    pub memo: Option<String>,
+    /// Wait for the transaction to be finalized before reporting
+    #[arg(long)]
+    pub wait_finalized: bool,
+}
+
+#[derive(Args)]
+pub struct CreateMintArgs {
+    #[arg(long, default_value_t = 2)]
+    pub decimals: u8,
+    /// Create the mint with the Token-2022 program
+    #[arg(long)]
+    pub token_2022: bool,
+    /// Lamports to fund the mint with, skips fetching the rent-exempt minimum
+    /// when signing offline
+    #[arg(long)]
+    pub lamports: Option<u64>,
+    #[command(flatten)]
+    pub extensions: MintExtensionArgs,
+}
+
+/// Token-2022 mint extensions, all require `--token-2022`.
+#[derive(Args)]
+pub struct MintExtensionArgs {
+    /// Transfer fee in basis points
+    #[arg(long, requires_all = ["token_2022", "max_transfer_fee"])]
+    pub transfer_fee_basis_points: Option<u16>,
+    /// Maximum transfer fee in base units
+    #[arg(long, requires = "transfer_fee_basis_points")]
+    pub max_transfer_fee: Option<u64>,
+    /// Store metadata in the mint itself, via the metadata pointer extension
+    #[arg(long, requires_all = ["token_2022", "metadata_symbol", "metadata_uri"])]
+    pub metadata_name: Option<String>,
+    #[arg(long, requires = "metadata_name")]
+    pub metadata_symbol: Option<String>,
+    #[arg(long, requires = "metadata_name")]
+    pub metadata_uri: Option<String>,
+    /// Make tokens soulbound to the account they are minted to
+    #[arg(long, requires = "token_2022")]
+    pub non_transferable: bool,
+    /// Interest rate in basis points
+    #[arg(long, requires = "token_2022", allow_negative_numbers = true)]
+    pub interest_rate: Option<i16>,
+}
+
+#[derive(Args)]
+pub struct MintArgs {
+    #[arg(long)]
+    pub mint: Pubkey,
+    /// Token account receiving the tokens
+    #[arg(long)]
+    pub to: Pubkey,
+    /// Amount in tokens, e.g. 12.5, converted using the mint's decimals
+    #[arg(long)]
+    pub amount: UiAmount,
+    /// Mint decimals, skips fetching the mint when signing offline
+    #[arg(long)]
+    pub decimals: Option<u8>,
+    /// Mint authority, defaults to the payer. May be an SPL multisig account
+    #[arg(long)]
+    pub mint_authority: Option<Pubkey>,
+    /// Signer of a multisig mint authority, may be repeated
+    #[arg(long = "multisig-signer", requires = "mint_authority")]
+    pub multisig_signers: Vec<Pubkey>,
+    /// The mint belongs to the Token-2022 program
+    #[arg(long)]
+    pub token_2022: bool,
+}
+
+#[derive(Args)]
+pub struct CreateAtaArgs {
+    #[arg(long)]
+    pub mint: Pubkey,
+    /// Wallet owning the new account, defaults to the payer
+    #[arg(long)]
+    pub owner: Option<Pubkey>,
+    /// The mint belongs to the Token-2022 program
+    #[arg(long)]
+    pub token_2022: bool,
+}
+
+#[derive(Args)]
+pub struct CreateMetadataArgs {
+    #[arg(long)]
+    pub mint: Pubkey,
+    #[arg(long)]
+    pub name: String,
+    #[arg(long)]
+    pub symbol: String,
+    /// Link to the off-chain JSON metadata
+    #[arg(long)]
+    pub uri: String,
+    #[arg(long, default_value_t = 0)]
+    pub seller_fee_basis_points: u16,
+    /// Creator as ADDRESS:SHARE, may be repeated. Shares must add up to 100
+    #[arg(long = "creator")]
+    pub creators: Vec<CreatorArg>,
+    /// Collection mint, left unverified
+    #[arg(long)]
+    pub collection: Option<Pubkey>,
+    /// Make the metadata immutable
+    #[arg(long)]
+    pub immutable: bool,
+    /// Don't fetch and validate the JSON at the URI
+    #[arg(long)]
+    pub skip_uri_check: bool,
+}
+
+#[derive(Args)]
+pub struct ShowMetadataArgs {
+    #[arg(long)]
+    pub mint: Pubkey,
+}
+
+/// Fields that are left out keep their current on-chain value.
+#[derive(Args)]
+pub struct UpdateMetadataArgs {
+    #[arg(long)]
+    pub mint: Pubkey,
+    #[arg(long)]
+    pub name: Option<String>,
+    #[arg(long)]
+    pub symbol: Option<String>,
+    #[arg(long)]
+    pub uri: Option<String>,
+    #[arg(long)]
+    pub seller_fee_basis_points: Option<u16>,
+    /// Replaces the creators, as ADDRESS:SHARE, may be repeated
+    #[arg(long = "creator", conflicts_with = "clear_creators")]
+    pub creators: Vec<CreatorArg>,
+    #[arg(long)]
+    pub clear_creators: bool,
+    /// Collection mint, left unverified
+    #[arg(long, conflicts_with = "clear_collection")]
+    pub collection: Option<Pubkey>,
+    #[arg(long)]
+    pub clear_collection: bool,
+    #[arg(long)]
+    pub new_update_authority: Option<Pubkey>,
+    /// Make the metadata immutable. This cannot be undone
+    #[arg(long)]
+    pub immutable: bool,
+    /// Don't fetch and validate the JSON at the URI
+    #[arg(long)]
+    pub skip_uri_check: bool,
+}
+
+#[derive(Clone)]
+pub struct CreatorArg {
+    pub address: Pubkey,
+    pub share: u8,
+}
+
+impl FromStr for CreatorArg {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (address, share) = value
+            .split_once(':')
+            .ok_or_else(|| format!("expected ADDRESS:SHARE, got '{}'", value))?;
+        Ok(CreatorArg {
+            address: address
+                .parse()
+                .map_err(|_| format!("'{}' is not a valid address", address))?,
+            share: share
+                .parse()
+                .map_err(|_| format!("'{}' is not a valid share", share))?,
+        })
+    }
+}
@@ -0,0 +1,489 @@
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Serialize;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token_2022::instruction::{mint_to_checked, transfer_checked};
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    build_transaction, fits_in_transaction,
+    token::{fetch_mint_decimals, token_program_id},
+    tx,
+};
+use crate::{CliContext, amount::UiAmount, cli::DistributeArgs};
+
+const RESULTS_HEADER: &str = "address,amount,status,signature,error";
+
+#[derive(Serialize)]
+pub struct DistributeReport {
+    pub mint: String,
+    pub recipients: usize,
+    pub sent: usize,
+    pub already_sent: usize,
+    pub failed: usize,
+    pub transactions: usize,
+    pub results_file: String,
+}
+
+impl fmt::Display for DistributeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Distributed {} to {} recipients",
+            self.mint, self.recipients
+        )?;
+        writeln!(
+            f,
+            "Sent: {} in {} transactions, already sent: {}, failed: {}",
+            self.sent, self.transactions, self.already_sent, self.failed
+        )?;
+        write!(f, "Results: {}", self.results_file)
+    }
+}
+
+struct Recipient {
+    address: Pubkey,
+    amount: UiAmount,
+    raw_amount: u64,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum RowStatus {
+    Pending,
+    Sent,
+    Failed,
+}
+
+impl RowStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            RowStatus::Pending => "pending",
+            RowStatus::Sent => "sent",
+            RowStatus::Failed => "failed",
+        }
+    }
+}
+
+/// One line of the results file. Rows are matched to recipients by address
+/// and amount, so editing an amount in the CSV sends it again.
+struct ResultRow {
+    address: String,
+    amount: String,
+    status: RowStatus,
+    signature: String,
+    error: String,
+}
+
+fn read_recipients(path: &Path, decimals: u8) -> Result<Vec<Recipient>> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read recipients file {}", path.display()))?;
+    parse_recipients(&contents, decimals)
+}
+
+/// Parses `address,amount` lines, skipping blanks, `#` comments and a header.
+fn parse_recipients(contents: &str, decimals: u8) -> Result<Vec<Recipient>> {
+    let mut recipients = Vec::new();
+    let mut seen = HashSet::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [address, amount] = fields[..] else {
+            bail!("line {}: expected `address,amount`", line_number);
+        };
+        if address.eq_ignore_ascii_case("address") {
+            continue;
+        }
+        let address: Pubkey = address
+            .parse()
+            .map_err(|_| anyhow!("line {}: '{}' is not a valid address", line_number, address))?;
+        let amount: UiAmount = amount
+            .parse()
+            .with_context(|| format!("line {}", line_number))?;
+        let raw_amount = amount
+            .to_raw(decimals)
+            .with_context(|| format!("line {}", line_number))?;
+        if !seen.insert((address, amount.to_string())) {
+            bail!(
+                "line {}: {} already receives {} on an earlier line",
+                line_number,
+                address,
+                amount
+            );
+        }
+        recipients.push(Recipient {
+            address,
+            amount,
+            raw_amount,
+        });
+    }
+    Ok(recipients)
+}
+
+/// Rows already sent by a previous run, keyed by address and amount.
+fn read_sent_rows(path: &Path) -> Result<Vec<ResultRow>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read results file {}", path.display()))?;
+    Ok(parse_sent_rows(&contents))
+}
+
+fn parse_sent_rows(contents: &str) -> Vec<ResultRow> {
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.splitn(5, ',').collect();
+            match fields[..] {
+                [address, amount, "sent", signature, ..] => Some(ResultRow {
+                    address: address.to_string(),
+                    amount: amount.to_string(),
+                    status: RowStatus::Sent,
+                    signature: signature.to_string(),
+                    error: String::new(),
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// One row per recipient, carrying over the status and signature of rows a
+/// previous run already sent.
+fn resume_rows(recipients: &[Recipient], sent_rows: &[ResultRow]) -> Vec<ResultRow> {
+    recipients
+        .iter()
+        .map(|recipient| {
+            let address = recipient.address.to_string();
+            let amount = recipient.amount.to_string();
+            let previous = sent_rows
+                .iter()
+                .find(|row| row.address == address && row.amount == amount);
+            ResultRow {
+                status: previous.map_or(RowStatus::Pending, |row| row.status),
+                signature: previous
+                    .map(|row| row.signature.clone())
+                    .unwrap_or_default(),
+                error: String::new(),
+                address,
+                amount,
+            }
+        })
+        .collect()
+}
+
+/// Rewrites the whole file through a temporary one, so an interrupted run
+/// never leaves it half written.
+fn write_results(path: &Path, rows: &[ResultRow]) -> Result<()> {
+    let mut contents = format!("{}\n", RESULTS_HEADER);
+    for row in rows {
+        contents.push_str(&format!(
+            "{},{},{},{},{}\n",
+            row.address,
+            row.amount,
+            row.status.as_str(),
+            row.signature,
+            row.error.replace([',', '\n', '\r'], " ")
+        ));
+    }
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, contents)
+        .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn default_results_path(recipients: &Path) -> PathBuf {
+    let mut file_name = recipients.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".results.csv");
+    recipients.with_file_name(file_name)
+}
+
+/// Creates the recipient's ATA if needed, then mints or transfers to it.
+fn recipient_instructions(
+    context: &CliContext,
+    args: &DistributeArgs,
+    decimals: u8,
+    recipient: &Recipient,
+) -> Result<Vec<Instruction>> {
+    let payer = context.payer.pubkey();
+    let program_id = token_program_id(args.token_2022);
+    let destination =
+        get_associated_token_address_with_program_id(&recipient.address, &args.mint, &program_id);
+
+    let create_ata = create_associated_token_account_idempotent(
+        &payer,
+        &recipient.address,
+        &args.mint,
+        &program_id,
+    );
+    let send = if args.mint_new_tokens {
+        mint_to_checked(
+            &program_id,
+            &args.mint,
+            &destination,
+            &payer,
+            &[],
+            recipient.raw_amount,
+            decimals,
+        )?
+    } else {
+        let source = get_associated_token_address_with_program_id(&payer, &args.mint, &program_id);
+        transfer_checked(
+            &program_id,
+            &source,
+            &args.mint,
+            &destination,
+            &payer,
+            &[],
+            recipient.raw_amount,
+            decimals,
+        )?
+    };
+    Ok(vec![create_ata, send])
+}
+
+/// Groups recipients, by index, into as few transactions as `fits` allows,
+/// usually `fits_in_transaction`.
+fn pack_batches(
+    pending: &[(usize, Vec<Instruction>)],
+    fits: impl Fn(&[Instruction]) -> Result<bool>,
+) -> Result<Vec<Vec<usize>>> {
+    let mut batches: Vec<Vec<usize>> = Vec::new();
+    let mut batch: Vec<usize> = Vec::new();
+    let mut batch_instructions: Vec<Instruction> = Vec::new();
+    for (index, instructions) in pending {
+        let mut candidate = batch_instructions.clone();
+        candidate.extend_from_slice(instructions);
+        if !batch.is_empty() && !fits(&candidate)? {
+            batches.push(std::mem::take(&mut batch));
+            candidate = instructions.clone();
+        }
+        batch.push(*index);
+        batch_instructions = candidate;
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    Ok(batches)
+}
+
+/// Re-sends the same signed transaction while its blockhash is valid, so at
+/// most one copy can ever land. A new one is only signed once the blockhash
+/// has expired and the previous copy is known not to have landed.
+fn send_with_retries(
+    context: &CliContext,
+    instructions: &[Instruction],
+    max_retries: u32,
+) -> Result<Signature> {
+    let mut tx = build_transaction(context, instructions, &[])?;
+    tx::ensure_fully_signed(&tx)?;
+    let mut last_error = None;
+    for _ in 0..=max_retries {
+        let signature = tx.signatures[0];
+        match context.client.send_and_confirm_transaction(&tx) {
+            Ok(signature) => return Ok(signature),
+            Err(error) => last_error = Some(error),
+        }
+        if let Some(result) = context.client.get_signature_status(&signature)? {
+            return result.map(|()| signature).map_err(Into::into);
+        }
+        let blockhash = tx.message.recent_blockhash;
+        if !context
+            .client
+            .is_blockhash_valid(&blockhash, context.commitment)?
+        {
+            // It may have landed just before expiring.
+            if let Some(result) = context.client.get_signature_status(&signature)? {
+                return result.map(|()| signature).map_err(Into::into);
+            }
+            tx = build_transaction(context, instructions, &[])?;
+            tx::ensure_fully_signed(&tx)?;
+        }
+    }
+    Err(last_error.map_or_else(|| anyhow!("Transaction was not sent"), Into::into))
+}
+
+pub fn distribute(context: &CliContext, args: DistributeArgs) -> Result<()> {
+    if context.tx.sign_only {
+        bail!("token distribute sends many transactions and can't be used with --sign-only");
+    }
+    if context.tx.nonce.is_some() {
+        // A nonce value stays valid until it is advanced, so a retry could
+        // never tell whether an earlier copy is still going to land.
+        bail!("token distribute retries failed batches and can't be used with --nonce");
+    }
+    let decimals = match args.decimals {
+        Some(decimals) => decimals,
+        None => fetch_mint_decimals(context, &args.mint)?,
+    };
+    let recipients = read_recipients(&args.recipients, decimals)?;
+    let results_path = args
+        .results
+        .clone()
+        .unwrap_or_else(|| default_results_path(&args.recipients));
+    let sent_rows = read_sent_rows(&results_path)?;
+
+    let mut rows = resume_rows(&recipients, &sent_rows);
+    let already_sent = rows
+        .iter()
+        .filter(|row| row.status == RowStatus::Sent)
+        .count();
+
+    let mut pending = Vec::new();
+    for (index, recipient) in recipients.iter().enumerate() {
+        if rows[index].status != RowStatus::Sent {
+            pending.push((
+                index,
+                recipient_instructions(context, &args, decimals, recipient)?,
+            ));
+        }
+    }
+    let batches = pack_batches(&pending, |instructions| {
+        fits_in_transaction(context, instructions)
+    })?;
+    write_results(&results_path, &rows)?;
+
+    let (mut sent, mut failed, mut transactions) = (0, 0, 0);
+    for batch in &batches {
+        let instructions: Vec<Instruction> = pending
+            .iter()
+            .filter(|(index, _)| batch.contains(index))
+            .flat_map(|(_, instructions)| instructions.clone())
+            .collect();
+        let outcome = send_with_retries(context, &instructions, args.max_retries);
+        for &index in batch {
+            let row = &mut rows[index];
+            match &outcome {
+                Ok(signature) => {
+                    row.status = RowStatus::Sent;
+                    row.signature = signature.to_string();
+                    row.error.clear();
+                }
+                Err(error) => {
+                    row.status = RowStatus::Failed;
+                    row.error = format!("{:#}", error);
+                }
+            }
+        }
+        match outcome {
+            Ok(_) => {
+                sent += batch.len();
+                transactions += 1;
+            }
+            Err(_) => failed += batch.len(),
+        }
+        write_results(&results_path, &rows)?;
+    }
+
+    context.output.print(&DistributeReport {
+        mint: args.mint.to_string(),
+        recipients: recipients.len(),
+        sent,
+        already_sent,
+        failed,
+        transactions,
+        results_file: results_path.display().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "3ZPcth6Uk1JrxhhzQr9Q2diDrjYDdAqTWhdcrDPENtha";
+    const BOB: &str = "7gE3KxG74TTQzHMdBX2XpQXetHTr2gAfrxkYPqLvKKvf";
+
+    fn parse_error(contents: &str) -> String {
+        format!("{:#}", parse_recipients(contents, 2).err().unwrap())
+    }
+
+    #[test]
+    fn parses_recipients_skipping_header_comments_and_blanks() {
+        let contents = format!(
+            "address,amount\n# airdrop round 1\n\n{}, 10\n  {},2.5  \n",
+            ALICE, BOB
+        );
+        let recipients = parse_recipients(&contents, 2).unwrap();
+        let parsed: Vec<(String, String, u64)> = recipients
+            .iter()
+            .map(|r| (r.address.to_string(), r.amount.to_string(), r.raw_amount))
+            .collect();
+        assert_eq!(
+            parsed,
+            [
+                (ALICE.to_string(), "10".to_string(), 1000),
+                (BOB.to_string(), "2.5".to_string(), 250),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_recipient_lines() {
+        assert!(parse_error(ALICE).starts_with("line 1: expected `address,amount`"));
+        assert!(parse_error("nobody,1").starts_with("line 1: 'nobody' is not a valid address"));
+        assert!(parse_error(&format!("{},1.234", ALICE)).starts_with("line 1:"));
+        assert!(
+            parse_error(&format!("{},1\n{},1.0", ALICE, ALICE)).starts_with(
+                "line 2: 3ZPcth6Uk1JrxhhzQr9Q2diDrjYDdAqTWhdcrDPENtha already receives 1"
+            )
+        );
+    }
+
+    #[test]
+    fn resumes_only_rows_sent_with_the_same_amount() {
+        let results = format!(
+            "{}\n{},10,sent,5sig,\n{},2.5,failed,,timed out\n",
+            RESULTS_HEADER, ALICE, BOB
+        );
+        let sent_rows = parse_sent_rows(&results);
+        assert_eq!(sent_rows.len(), 1);
+
+        let recipients = parse_recipients(&format!("{},10\n{},2.5", ALICE, BOB), 2).unwrap();
+        let rows = resume_rows(&recipients, &sent_rows);
+        assert!(rows[0].status == RowStatus::Sent);
+        assert_eq!(rows[0].signature, "5sig");
+        assert!(rows[1].status == RowStatus::Pending);
+
+        // Changing Alice's amount sends it again.
+        let recipients = parse_recipients(&format!("{},12", ALICE), 2).unwrap();
+        let rows = resume_rows(&recipients, &sent_rows);
+        assert!(rows[0].status == RowStatus::Pending);
+        assert!(rows[0].signature.is_empty());
+    }
+
+    #[test]
+    fn packs_recipients_into_as_few_batches_as_fit() {
+        let program_id = Pubkey::new_unique();
+        let pending: Vec<(usize, Vec<Instruction>)> = [0, 2, 3, 5, 6]
+            .into_iter()
+            .map(|index| {
+                let instruction = Instruction::new_with_bytes(program_id, &[], vec![]);
+                (index, vec![instruction.clone(), instruction])
+            })
+            .collect();
+
+        let batches = pack_batches(&pending, |instructions| Ok(instructions.len() <= 4)).unwrap();
+        assert_eq!(batches, [vec![0, 2], vec![3, 5], vec![6]]);
+
+        // A recipient that fits nowhere still gets a transaction of its own.
+        let batches = pack_batches(&pending, |_| Ok(false)).unwrap();
+        assert_eq!(batches.len(), pending.len());
+        assert!(pack_batches(&[], |_| Ok(true)).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,421 @@
+use anyhow::{Context, Result, bail};
+use mpl_token_metadata::{
+    ID as TOKEN_METADATA_PROGRAM_ID, MAX_CREATOR_LIMIT, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH,
+    MAX_URI_LENGTH,
+    accounts::Metadata,
+    instructions::{
+        CreateMetadataAccountV3, CreateMetadataAccountV3InstructionArgs, UpdateMetadataAccountV2,
+        UpdateMetadataAccountV2InstructionArgs,
+    },
+    types::{Collection, Creator, DataV2},
+};
+use serde::Serialize;
+use solana_sdk::{pubkey::Pubkey, signature::Signer, system_program};
+use std::fmt;
+
+use super::send_instructions;
+use crate::{
+    CliContext,
+    cli::{CreateMetadataArgs, CreatorArg, ShowMetadataArgs, UpdateMetadataArgs},
+    metadata_json,
+};
+
+#[derive(Serialize)]
+pub struct CreateMetadataReport {
+    pub mint: String,
+    pub metadata: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub is_mutable: bool,
+    pub signature: String,
+    pub transaction_explorer: String,
+    pub explorer: String,
+}
+
+impl fmt::Display for CreateMetadataReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Metadata account: {}", self.metadata)?;
+        writeln!(f, "Name: {} ({})", self.name, self.symbol)?;
+        writeln!(f, "URI: {}", self.uri)?;
+        writeln!(f, "Transaction signature: {}", self.transaction_explorer)?;
+        write!(f, "Look at the token mint again: {}", self.explorer)
+    }
+}
+
+#[derive(Serialize)]
+pub struct CreatorView {
+    pub address: String,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[derive(Serialize)]
+pub struct CollectionView {
+    pub key: String,
+    pub verified: bool,
+}
+
+/// A decoded metadata account, with the padding stripped from the strings.
+#[derive(Serialize)]
+pub struct MetadataView {
+    pub mint: String,
+    pub metadata: String,
+    pub update_authority: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<CreatorView>,
+    pub collection: Option<CollectionView>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub token_standard: Option<String>,
+}
+
+impl MetadataView {
+    fn new(metadata_address: Pubkey, metadata: &Metadata) -> Self {
+        MetadataView {
+            mint: metadata.mint.to_string(),
+            metadata: metadata_address.to_string(),
+            update_authority: metadata.update_authority.to_string(),
+            name: trim_padding(&metadata.name).to_string(),
+            symbol: trim_padding(&metadata.symbol).to_string(),
+            uri: trim_padding(&metadata.uri).to_string(),
+            seller_fee_basis_points: metadata.seller_fee_basis_points,
+            creators: metadata
+                .creators
+                .iter()
+                .flatten()
+                .map(|creator| CreatorView {
+                    address: creator.address.to_string(),
+                    verified: creator.verified,
+                    share: creator.share,
+                })
+                .collect(),
+            collection: metadata
+                .collection
+                .as_ref()
+                .map(|collection| CollectionView {
+                    key: collection.key.to_string(),
+                    verified: collection.verified,
+                }),
+            primary_sale_happened: metadata.primary_sale_happened,
+            is_mutable: metadata.is_mutable,
+            token_standard: metadata
+                .token_standard
+                .as_ref()
+                .map(|standard| format!("{:?}", standard)),
+        }
+    }
+}
+
+impl fmt::Display for MetadataView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Mint: {}", self.mint)?;
+        writeln!(f, "Metadata account: {}", self.metadata)?;
+        writeln!(f, "Update authority: {}", self.update_authority)?;
+        writeln!(f, "Name: {} ({})", self.name, self.symbol)?;
+        writeln!(f, "URI: {}", self.uri)?;
+        writeln!(f, "Seller fee: {} bps", self.seller_fee_basis_points)?;
+        for creator in &self.creators {
+            writeln!(
+                f,
+                "Creator: {} {}%{}",
+                creator.address,
+                creator.share,
+                if creator.verified { " (verified)" } else { "" }
+            )?;
+        }
+        if let Some(collection) = &self.collection {
+            writeln!(
+                f,
+                "Collection: {}{}",
+                collection.key,
+                if collection.verified {
+                    " (verified)"
+                } else {
+                    ""
+                }
+            )?;
+        }
+        if let Some(token_standard) = &self.token_standard {
+            writeln!(f, "Token standard: {}", token_standard)?;
+        }
+        writeln!(f, "Primary sale happened: {}", self.primary_sale_happened)?;
+        write!(f, "Mutable: {}", self.is_mutable)
+    }
+}
+
+#[derive(Serialize)]
+pub struct UpdateMetadataReport {
+    pub mint: String,
+    pub metadata: String,
+    pub signature: String,
+    pub explorer: String,
+}
+
+impl fmt::Display for UpdateMetadataReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Updated metadata account: {}", self.metadata)?;
+        write!(f, "Transaction signature: {}", self.explorer)
+    }
+}
+
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    let metadata_seeds = &[
+        b"metadata",
+        TOKEN_METADATA_PROGRAM_ID.as_ref(),
+        mint.as_ref(),
+    ];
+    Pubkey::find_program_address(metadata_seeds, &TOKEN_METADATA_PROGRAM_ID).0
+}
+
+/// On-chain strings are padded with NUL bytes up to their maximum length.
+fn trim_padding(value: &str) -> &str {
+    value.trim_end_matches('\0')
+}
+
+pub fn fetch_metadata(context: &CliContext, mint: &Pubkey) -> Result<(Pubkey, Metadata)> {
+    let metadata_pda = metadata_address(mint);
+    let account = context
+        .client
+        .get_account(&metadata_pda)
+        .with_context(|| format!("No metadata account found for mint {}", mint))?;
+    let metadata = Metadata::from_bytes(&account.data)
+        .with_context(|| format!("Failed to decode metadata account {}", metadata_pda))?;
+    Ok((metadata_pda, metadata))
+}
+
+/// Creators signed by the update authority are marked verified, as are
+/// creators already verified in `current`. Others have to verify themselves
+/// later.
+fn creators_from_args(
+    creators: &[CreatorArg],
+    update_authority: &Pubkey,
+    current: Option<&[Creator]>,
+) -> Result<Option<Vec<Creator>>> {
+    if creators.is_empty() {
+        return Ok(None);
+    }
+    if creators.len() > MAX_CREATOR_LIMIT {
+        bail!("At most {} creators are allowed", MAX_CREATOR_LIMIT);
+    }
+    let total_share: u32 = creators.iter().map(|creator| creator.share as u32).sum();
+    if total_share != 100 {
+        bail!("Creator shares must add up to 100, got {}", total_share);
+    }
+    Ok(Some(
+        creators
+            .iter()
+            .map(|creator| {
+                let already_verified = current
+                    .unwrap_or_default()
+                    .iter()
+                    .any(|existing| existing.address == creator.address && existing.verified);
+                Creator {
+                    address: creator.address,
+                    verified: already_verified || creator.address == *update_authority,
+                    share: creator.share,
+                }
+            })
+            .collect(),
+    ))
+}
+
+fn unverified_collection(mint: Pubkey) -> Collection {
+    Collection {
+        verified: false,
+        key: mint,
+    }
+}
+
+fn validate_data(data: &DataV2, skip_uri_check: bool) -> Result<()> {
+    if data.name.len() > MAX_NAME_LENGTH {
+        bail!("Name must be at most {} bytes", MAX_NAME_LENGTH);
+    }
+    if data.symbol.len() > MAX_SYMBOL_LENGTH {
+        bail!("Symbol must be at most {} bytes", MAX_SYMBOL_LENGTH);
+    }
+    if data.uri.len() > MAX_URI_LENGTH {
+        bail!("URI must be at most {} bytes", MAX_URI_LENGTH);
+    }
+    if data.seller_fee_basis_points > 10_000 {
+        bail!("Seller fee must be at most 10000 basis points");
+    }
+    if !skip_uri_check {
+        metadata_json::validate_uri(&data.uri, &data.name, &data.symbol)?;
+    }
+    Ok(())
+}
+
+pub fn create(context: &CliContext, args: CreateMetadataArgs) -> Result<()> {
+    let payer = context.payer.pubkey();
+    let metadata_pda = metadata_address(&args.mint);
+
+    let metadata_data = DataV2 {
+        name: args.name.clone(),
+        symbol: args.symbol.clone(),
+        uri: args.uri.clone(),
+        seller_fee_basis_points: args.seller_fee_basis_points,
+        creators: creators_from_args(&args.creators, &payer, None)?,
+        collection: args.collection.map(unverified_collection),
+        uses: None,
+    };
+    validate_data(&metadata_data, args.skip_uri_check)?;
+
+    let create_instruction = CreateMetadataAccountV3 {
+        metadata: metadata_pda,
+        mint: args.mint,
+        mint_authority: payer,
+        payer,
+        update_authority: (payer, true),
+        system_program: system_program::ID,
+        rent: None,
+    }
+    .instruction(CreateMetadataAccountV3InstructionArgs {
+        data: metadata_data,
+        is_mutable: !args.immutable,
+        collection_details: None,
+    });
+
+    let Some(signature) = send_instructions(context, &[create_instruction], &[])? else {
+        return Ok(());
+    };
+    context.output.print(&CreateMetadataReport {
+        mint: args.mint.to_string(),
+        metadata: metadata_pda.to_string(),
+        name: args.name,
+        symbol: args.symbol,
+        uri: args.uri,
+        is_mutable: !args.immutable,
+        signature: signature.to_string(),
+        transaction_explorer: context.cluster.explorer_tx_url(signature),
+        explorer: context.cluster.explorer_address_url(args.mint),
+    })
+}
+
+pub fn show(context: &CliContext, args: ShowMetadataArgs) -> Result<()> {
+    let (metadata_pda, metadata) = fetch_metadata(context, &args.mint)?;
+    context
+        .output
+        .print(&MetadataView::new(metadata_pda, &metadata))
+}
+
+pub fn update(context: &CliContext, args: UpdateMetadataArgs) -> Result<()> {
+    let payer = context.payer.pubkey();
+    let (metadata_pda, current) = fetch_metadata(context, &args.mint)?;
+    if !current.is_mutable {
+        bail!("Metadata of {} is immutable", args.mint);
+    }
+    if current.update_authority != payer {
+        bail!(
+            "{} is not the update authority, {} is",
+            payer,
+            current.update_authority
+        );
+    }
+
+    let creators = if args.clear_creators {
+        None
+    } else if args.creators.is_empty() {
+        current.creators.clone()
+    } else {
+        creators_from_args(&args.creators, &payer, current.creators.as_deref())?
+    };
+    let collection = if args.clear_collection {
+        None
+    } else {
+        args.collection
+            .map(unverified_collection)
+            .or(current.collection.clone())
+    };
+    let data = DataV2 {
+        name: args
+            .name
+            .unwrap_or_else(|| trim_padding(&current.name).to_string()),
+        symbol: args
+            .symbol
+            .unwrap_or_else(|| trim_padding(&current.symbol).to_string()),
+        uri: args
+            .uri
+            .unwrap_or_else(|| trim_padding(&current.uri).to_string()),
+        seller_fee_basis_points: args
+            .seller_fee_basis_points
+            .unwrap_or(current.seller_fee_basis_points),
+        creators,
+        collection,
+        uses: current.uses.clone(),
+    };
+    let data_changed = data.name != trim_padding(&current.name)
+        || data.symbol != trim_padding(&current.symbol)
+        || data.uri != trim_padding(&current.uri)
+        || data.seller_fee_basis_points != current.seller_fee_basis_points
+        || data.creators != current.creators
+        || data.collection != current.collection;
+    if data_changed {
+        validate_data(&data, args.skip_uri_check)?;
+    }
+
+    let update_args = UpdateMetadataAccountV2InstructionArgs {
+        data: data_changed.then_some(data),
+        new_update_authority: args.new_update_authority,
+        primary_sale_happened: None,
+        is_mutable: args.immutable.then_some(false),
+    };
+    if update_args.data.is_none()
+        && update_args.new_update_authority.is_none()
+        && update_args.is_mutable.is_none()
+    {
+        bail!("Nothing to update");
+    }
+
+    let update_instruction = UpdateMetadataAccountV2 {
+        metadata: metadata_pda,
+        update_authority: payer,
+    }
+    .instruction(update_args);
+
+    let Some(signature) = send_instructions(context, &[update_instruction], &[])? else {
+        return Ok(());
+    };
+    context.output.print(&UpdateMetadataReport {
+        mint: args.mint.to_string(),
+        metadata: metadata_pda.to_string(),
+        signature: signature.to_string(),
+        explorer: context.cluster.explorer_tx_url(signature),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_verified_creators_when_replacing_creators() {
+        let update_authority = Pubkey::new_unique();
+        let verified = Pubkey::new_unique();
+        let unverified = Pubkey::new_unique();
+        let added = Pubkey::new_unique();
+        let current = [
+            Creator {
+                address: verified,
+                verified: true,
+                share: 50,
+            },
+            Creator {
+                address: unverified,
+                verified: false,
+                share: 50,
+            },
+        ];
+        let args = [verified, unverified, added, update_authority]
+            .map(|address| CreatorArg { address, share: 25 });
+
+        let creators = creators_from_args(&args, &update_authority, Some(&current))
+            .unwrap()
+            .unwrap();
+        let verified_flags: Vec<bool> = creators.iter().map(|creator| creator.verified).collect();
+        assert_eq!(verified_flags, [true, false, false, true]);
+    }
+}
@@ -0,0 +1,105 @@
+pub mod distribute;
+pub mod metadata;
+pub mod sol;
+pub mod token;
+pub mod tx;
+
+use anyhow::{Context, Result, bail};
+use solana_client::nonce_utils;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::Instruction,
+    message::Message,
+    packet::PACKET_DATA_SIZE,
+    signature::{Keypair, Signature, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+
+use crate::{
+    CliContext,
+    compute_budget::{MAX_COMPUTE_UNIT_LIMIT, compute_budget_instructions},
+};
+use tx::SignOnlyReport;
+
+/// Prepends any requested ComputeBudget instructions and the nonce advance,
+/// then signs with every available keypair plus `extra_signers`.
+pub fn build_transaction(
+    context: &CliContext,
+    instructions: &[Instruction],
+    extra_signers: &[&Keypair],
+) -> Result<Transaction> {
+    let payer = context.payer.pubkey();
+    let mut tx_instructions = compute_budget_instructions(context, instructions, &payer)?;
+    tx_instructions.extend_from_slice(instructions);
+    if let Some(nonce) = &context.tx.nonce {
+        // Advancing the nonce has to be the first instruction.
+        let authority = context.nonce_authority().pubkey();
+        tx_instructions.insert(
+            0,
+            system_instruction::advance_nonce_account(nonce, &authority),
+        );
+    }
+    let blockhash = recent_blockhash(context)?;
+
+    let mut tx = Transaction::new_unsigned(Message::new(&tx_instructions, Some(&payer)));
+    let mut signers = context.available_signers();
+    signers.extend_from_slice(extra_signers);
+    tx::partial_sign(&mut tx, &signers, blockhash)?;
+    Ok(tx)
+}
+
+/// Builds the transaction and, unless `--sign-only` is set, sends it and
+/// waits for confirmation. Returns `None` when the transaction was only
+/// signed and printed.
+pub fn send_instructions(
+    context: &CliContext,
+    instructions: &[Instruction],
+    extra_signers: &[&Keypair],
+) -> Result<Option<Signature>> {
+    let tx = build_transaction(context, instructions, extra_signers)?;
+    if context.tx.sign_only {
+        context.output.print(&SignOnlyReport::new(&tx)?)?;
+        return Ok(None);
+    }
+    tx::ensure_fully_signed(&tx)?;
+    Ok(Some(context.client.send_and_confirm_transaction(&tx)?))
+}
+
+/// Whether `instructions` fit in one transaction packet, leaving room for
+/// the ComputeBudget and nonce instructions `build_transaction` may add.
+pub fn fits_in_transaction(context: &CliContext, instructions: &[Instruction]) -> Result<bool> {
+    let payer = context.payer.pubkey();
+    let mut tx_instructions = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+        ComputeBudgetInstruction::set_compute_unit_price(0),
+    ];
+    if let Some(nonce) = &context.tx.nonce {
+        let authority = context.nonce_authority().pubkey();
+        tx_instructions.push(system_instruction::advance_nonce_account(nonce, &authority));
+    }
+    tx_instructions.extend_from_slice(instructions);
+    let tx = Transaction::new_unsigned(Message::new(&tx_instructions, Some(&payer)));
+    Ok(bincode::serialized_size(&tx)? as usize <= PACKET_DATA_SIZE)
+}
+
+/// `--blockhash` if given, the stored nonce when using a durable nonce,
+/// otherwise the latest blockhash.
+fn recent_blockhash(context: &CliContext) -> Result<Hash> {
+    if let Some(blockhash) = context.tx.blockhash {
+        return Ok(blockhash);
+    }
+    if let Some(nonce) = &context.tx.nonce {
+        let account =
+            nonce_utils::get_account_with_commitment(&context.client, nonce, context.commitment)
+                .with_context(|| format!("Failed to fetch nonce account {}", nonce))?;
+        let data = nonce_utils::data_from_account(&account)
+            .with_context(|| format!("{} is not an initialized nonce account", nonce))?;
+        return Ok(data.blockhash());
+    }
+    if context.tx.sign_only {
+        bail!("--sign-only needs --blockhash or --nonce");
+    }
+    Ok(context.client.get_latest_blockhash()?)
+}
@@ -0,0 +1,145 @@
+use anyhow::{Result, bail};
+use serde::Serialize;
+use solana_sdk::{pubkey::Pubkey, signature::Signer, system_instruction};
+use spl_memo::build_memo;
+use std::{collections::HashMap, fmt};
+
+use super::{fits_in_transaction, send_instructions};
+use crate::{
+    CliContext,
+    amount::{SOL_DECIMALS, format_amount},
+    cli::SendSolArgs,
+    confirmation::{self, Confirmation},
+};
+
+#[derive(Serialize)]
+pub struct Transfer {
+    pub recipient: String,
+    pub lamports: u64,
+}
+
+#[derive(Serialize)]
+pub struct SendSolReport {
+    pub sender: String,
+    pub transfers: Vec<Transfer>,
+    pub total_lamports: u64,
+    pub memo: Option<String>,
+    pub signature: String,
+    pub confirmation: Confirmation,
+    pub explorer: String,
+}
+
+impl fmt::Display for SendSolReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for transfer in &self.transfers {
+            writeln!(
+                f,
+                "Sent {} SOL from {} to {}",
+                format_amount(transfer.lamports, SOL_DECIMALS),
+                self.sender,
+                transfer.recipient
+            )?;
+        }
+        if self.transfers.len() > 1 {
+            writeln!(
+                f,
+                "Total: {} SOL",
+                format_amount(self.total_lamports, SOL_DECIMALS)
+            )?;
+        }
+        if let Some(memo) = &self.memo {
+            writeln!(f, "Memo: {}", memo)?;
+        }
+        writeln!(f, "Transaction signature: {}", self.signature)?;
+        writeln!(
+            f,
+            "Slot: {}, fee: {} SOL, status: {}",
+            self.confirmation.slot,
+            format_amount(self.confirmation.fee, SOL_DECIMALS),
+            self.confirmation.status
+        )?;
+        write!(f, "Explorer: {}", self.explorer)
+    }
+}
+
+/// Pairs recipients with amounts, a single amount applying to all of them.
+fn transfers_from_args(args: &SendSolArgs) -> Result<Vec<(Pubkey, u64)>> {
+    let amounts = match args.amounts.len() {
+        1 => vec![&args.amounts[0]; args.recipients.len()],
+        n if n == args.recipients.len() => args.amounts.iter().collect(),
+        n => bail!(
+            "Got {} amounts for {} recipients, pass one amount or one per recipient",
+            n,
+            args.recipients.len()
+        ),
+    };
+    args.recipients
+        .iter()
+        .zip(amounts)
+        .map(|(recipient, amount)| Ok((*recipient, amount.to_raw(SOL_DECIMALS)?)))
+        .collect()
+}
+
+/// A transfer to an account that doesn't exist yet fails unless it leaves
+/// the account rent exempt.
+fn check_rent_exemption(context: &CliContext, transfers: &[(Pubkey, u64)]) -> Result<()> {
+    let mut received: HashMap<Pubkey, u64> = HashMap::new();
+    for (recipient, lamports) in transfers {
+        *received.entry(*recipient).or_default() += lamports;
+    }
+    let recipients: Vec<Pubkey> = received.keys().copied().collect();
+    let accounts = context.client.get_multiple_accounts(&recipients)?;
+    let minimum = context.client.get_minimum_balance_for_rent_exemption(0)?;
+
+    for (recipient, account) in recipients.iter().zip(accounts) {
+        let balance = account.map_or(0, |account| account.lamports);
+        if balance == 0 && received[recipient] < minimum {
+            bail!(
+                "{} is a new account and needs at least {} SOL to be rent exempt, got {} SOL",
+                recipient,
+                format_amount(minimum, SOL_DECIMALS),
+                format_amount(received[recipient], SOL_DECIMALS)
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn send(context: &CliContext, args: SendSolArgs) -> Result<()> {
+    let sender = context.payer.pubkey();
+    let transfers = transfers_from_args(&args)?;
+    if !context.tx.sign_only {
+        check_rent_exemption(context, &transfers)?;
+    }
+
+    let mut instructions: Vec<_> = transfers
+        .iter()
+        .map(|(recipient, lamports)| system_instruction::transfer(&sender, recipient, *lamports))
+        .collect();
+    if let Some(memo) = &args.memo {
+        instructions.push(build_memo(memo.as_bytes(), &[&sender]));
+    }
+    if !fits_in_transaction(context, &instructions)? {
+        bail!("Too many recipients to fit in one transaction");
+    }
+
+    let Some(signature) = send_instructions(context, &instructions, &[])? else {
+        return Ok(());
+    };
+    let confirmation = confirmation::track(&context.client, &signature, args.wait_finalized)?;
+    context.output.print(&SendSolReport {
+        sender: sender.to_string(),
+        total_lamports: transfers.iter().map(|(_, lamports)| lamports).sum(),
+        transfers: transfers
+            .into_iter()
+            .map(|(recipient, lamports)| Transfer {
+                recipient: recipient.to_string(),
+                lamports,
+            })
+            .collect(),
+        memo: args.memo,
+        signature: signature.to_string(),
+        confirmation,
+        explorer: context.cluster.explorer_tx_url(signature),
+    })
+}
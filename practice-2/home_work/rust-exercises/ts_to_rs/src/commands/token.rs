@@ -0,0 +1,308 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id, instruction::create_associated_token_account,
+};
+use spl_token::ID as TOKEN_PROGRAM_ID;
+use spl_token_2022::{
+    ID as TOKEN_2022_PROGRAM_ID,
+    extension::{
+        ExtensionType, StateWithExtensions, interest_bearing_mint, metadata_pointer, transfer_fee,
+    },
+    instruction::{initialize_mint, initialize_non_transferable_mint, mint_to_checked},
+    state::Mint,
+};
+use spl_token_metadata_interface::state::TokenMetadata;
+use std::fmt;
+
+use super::send_instructions;
+use crate::{
+    CliContext,
+    amount::format_amount,
+    cli::{CreateAtaArgs, CreateMintArgs, MintArgs, MintExtensionArgs},
+};
+
+pub fn token_program_id(token_2022: bool) -> Pubkey {
+    if token_2022 {
+        TOKEN_2022_PROGRAM_ID
+    } else {
+        TOKEN_PROGRAM_ID
+    }
+}
+
+#[derive(Serialize)]
+pub struct CreateMintReport {
+    pub mint: String,
+    pub decimals: u8,
+    pub mint_authority: String,
+    pub token_program: String,
+    pub extensions: Vec<String>,
+    pub signature: String,
+    pub explorer: String,
+}
+
+impl fmt::Display for CreateMintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Token Mint: {}", self.mint)?;
+        writeln!(f, "Decimals: {}", self.decimals)?;
+        writeln!(f, "Mint authority: {}", self.mint_authority)?;
+        writeln!(f, "Token program: {}", self.token_program)?;
+        if !self.extensions.is_empty() {
+            writeln!(f, "Extensions: {}", self.extensions.join(", "))?;
+        }
+        writeln!(f, "Explorer: {}", self.explorer)?;
+        write!(f, "Tx signature: {}", self.signature)
+    }
+}
+
+#[derive(Serialize)]
+pub struct MintReport {
+    pub mint: String,
+    pub token_account: String,
+    pub amount: u64,
+    pub ui_amount: String,
+    pub decimals: u8,
+    pub signature: String,
+    pub explorer: String,
+}
+
+impl fmt::Display for MintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Minted {} tokens of {} to {}",
+            self.ui_amount, self.mint, self.token_account
+        )?;
+        write!(f, "Explorer: {}", self.explorer)
+    }
+}
+
+#[derive(Serialize)]
+pub struct CreateAtaReport {
+    pub mint: String,
+    pub owner: String,
+    pub token_account: String,
+    pub signature: String,
+    pub explorer: String,
+}
+
+impl fmt::Display for CreateAtaReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Token Account: {}", self.token_account)?;
+        writeln!(f, "Owner: {}", self.owner)?;
+        writeln!(f, "Explorer: {}", self.explorer)?;
+        write!(f, "Tx signature: {}", self.signature)
+    }
+}
+
+fn mint_extension_types(extensions: &MintExtensionArgs) -> Vec<ExtensionType> {
+    let mut extension_types = Vec::new();
+    if extensions.transfer_fee_basis_points.is_some() {
+        extension_types.push(ExtensionType::TransferFeeConfig);
+    }
+    if extensions.metadata_name.is_some() {
+        extension_types.push(ExtensionType::MetadataPointer);
+    }
+    if extensions.non_transferable {
+        extension_types.push(ExtensionType::NonTransferable);
+    }
+    if extensions.interest_rate.is_some() {
+        extension_types.push(ExtensionType::InterestBearingConfig);
+    }
+    extension_types
+}
+
+/// Extension initializers must run before `InitializeMint`.
+fn initialize_extension_instructions(
+    mint: &Pubkey,
+    authority: &Pubkey,
+    extensions: &MintExtensionArgs,
+) -> Result<Vec<Instruction>> {
+    let program_id = &TOKEN_2022_PROGRAM_ID;
+    let mut instructions = Vec::new();
+    if let (Some(basis_points), Some(max_fee)) = (
+        extensions.transfer_fee_basis_points,
+        extensions.max_transfer_fee,
+    ) {
+        instructions.push(transfer_fee::instruction::initialize_transfer_fee_config(
+            program_id,
+            mint,
+            Some(authority),
+            Some(authority),
+            basis_points,
+            max_fee,
+        )?);
+    }
+    if extensions.metadata_name.is_some() {
+        instructions.push(metadata_pointer::instruction::initialize(
+            program_id,
+            mint,
+            Some(*authority),
+            Some(*mint),
+        )?);
+    }
+    if extensions.non_transferable {
+        instructions.push(initialize_non_transferable_mint(program_id, mint)?);
+    }
+    if let Some(rate) = extensions.interest_rate {
+        instructions.push(interest_bearing_mint::instruction::initialize(
+            program_id,
+            mint,
+            Some(*authority),
+            rate,
+        )?);
+    }
+    Ok(instructions)
+}
+
+/// Metadata embedded in the mint, initialized after `InitializeMint`.
+fn embedded_metadata(mint: &Pubkey, extensions: &MintExtensionArgs) -> Option<TokenMetadata> {
+    Some(TokenMetadata {
+        mint: *mint,
+        name: extensions.metadata_name.clone()?,
+        symbol: extensions.metadata_symbol.clone()?,
+        uri: extensions.metadata_uri.clone()?,
+        ..Default::default()
+    })
+}
+
+pub fn create_mint(context: &CliContext, args: CreateMintArgs) -> Result<()> {
+    let payer = context.payer.pubkey();
+    let mint_keypair = Keypair::new();
+    let mint = mint_keypair.pubkey();
+    let program_id = token_program_id(args.token_2022);
+
+    let extension_types = mint_extension_types(&args.extensions);
+    let space = ExtensionType::try_calculate_account_len::<Mint>(&extension_types)?;
+    // The embedded metadata is variable length: the token program reallocs
+    // the mint when it is written, so only the rent for it is paid up front.
+    let metadata = embedded_metadata(&mint, &args.extensions);
+    let metadata_space = match &metadata {
+        Some(metadata) => metadata.tlv_size_of()?,
+        None => 0,
+    };
+    let lamports = match args.lamports {
+        Some(lamports) => lamports,
+        None => context
+            .client
+            .get_minimum_balance_for_rent_exemption(space + metadata_space)?,
+    };
+
+    let mut instructions = vec![system_instruction::create_account(
+        &payer,
+        &mint,
+        lamports,
+        space as u64,
+        &program_id,
+    )];
+    instructions.extend(initialize_extension_instructions(
+        &mint,
+        &payer,
+        &args.extensions,
+    )?);
+    instructions.push(initialize_mint(
+        &program_id,
+        &mint,
+        &payer,
+        None,
+        args.decimals,
+    )?);
+    if let Some(metadata) = metadata {
+        instructions.push(spl_token_metadata_interface::instruction::initialize(
+            &program_id,
+            &mint,
+            &payer,
+            &mint,
+            &payer,
+            metadata.name,
+            metadata.symbol,
+            metadata.uri,
+        ));
+    }
+
+    let Some(signature) = send_instructions(context, &instructions, &[&mint_keypair])? else {
+        return Ok(());
+    };
+    context.output.print(&CreateMintReport {
+        mint: mint.to_string(),
+        decimals: args.decimals,
+        mint_authority: payer.to_string(),
+        token_program: program_id.to_string(),
+        extensions: extension_types
+            .iter()
+            .map(|extension| format!("{:?}", extension))
+            .collect(),
+        signature: signature.to_string(),
+        explorer: context.cluster.explorer_address_url(mint),
+    })
+}
+
+/// Reads the decimals of a classic or Token-2022 mint.
+pub fn fetch_mint_decimals(context: &CliContext, mint: &Pubkey) -> Result<u8> {
+    let account = context
+        .client
+        .get_account(mint)
+        .with_context(|| format!("Failed to fetch mint {}", mint))?;
+    let state = StateWithExtensions::<Mint>::unpack(&account.data)
+        .with_context(|| format!("{} is not a token mint", mint))?;
+    Ok(state.base.decimals)
+}
+
+pub fn mint(context: &CliContext, args: MintArgs) -> Result<()> {
+    let decimals = match args.decimals {
+        Some(decimals) => decimals,
+        None => fetch_mint_decimals(context, &args.mint)?,
+    };
+    let amount = args.amount.to_raw(decimals)?;
+    let mint_authority = args.mint_authority.unwrap_or(context.payer.pubkey());
+    let multisig_signers: Vec<&Pubkey> = args.multisig_signers.iter().collect();
+    let mint_instruction = mint_to_checked(
+        &token_program_id(args.token_2022),
+        &args.mint,
+        &args.to,
+        &mint_authority,
+        &multisig_signers,
+        amount,
+        decimals,
+    )?;
+
+    let Some(signature) = send_instructions(context, &[mint_instruction], &[])? else {
+        return Ok(());
+    };
+    context.output.print(&MintReport {
+        mint: args.mint.to_string(),
+        token_account: args.to.to_string(),
+        amount,
+        ui_amount: format_amount(amount, decimals),
+        decimals,
+        signature: signature.to_string(),
+        explorer: context.cluster.explorer_tx_url(signature),
+    })
+}
+
+pub fn create_ata(context: &CliContext, args: CreateAtaArgs) -> Result<()> {
+    let payer = context.payer.pubkey();
+    let owner = args.owner.unwrap_or(payer);
+    let program_id = token_program_id(args.token_2022);
+    let associated_token =
+        get_associated_token_address_with_program_id(&owner, &args.mint, &program_id);
+
+    let instruction = create_associated_token_account(&payer, &owner, &args.mint, &program_id);
+
+    let Some(signature) = send_instructions(context, &[instruction], &[])? else {
+        return Ok(());
+    };
+    context.output.print(&CreateAtaReport {
+        mint: args.mint.to_string(),
+        owner: owner.to_string(),
+        token_account: associated_token.to_string(),
+        signature: signature.to_string(),
+        explorer: context.cluster.explorer_address_url(associated_token),
+    })
+}
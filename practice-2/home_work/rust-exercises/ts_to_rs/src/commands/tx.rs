@@ -0,0 +1,139 @@
+use anyhow::{Context, Result, bail};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use std::fmt;
+
+use crate::{CliContext, cli::TransactionArgs, config::Cluster, output::OutputFormat};
+
+#[derive(Serialize)]
+pub struct SignOnlyReport {
+    pub transaction: String,
+    pub blockhash: String,
+    pub signers: Vec<String>,
+    pub missing_signers: Vec<String>,
+}
+
+impl SignOnlyReport {
+    pub fn new(tx: &Transaction) -> Result<Self> {
+        let (signed, missing): (Vec<_>, Vec<_>) = signer_statuses(tx)
+            .into_iter()
+            .partition(|(_, is_signed)| *is_signed);
+        Ok(SignOnlyReport {
+            transaction: encode_transaction(tx)?,
+            blockhash: tx.message.recent_blockhash.to_string(),
+            signers: signed.iter().map(|(key, _)| key.to_string()).collect(),
+            missing_signers: missing.iter().map(|(key, _)| key.to_string()).collect(),
+        })
+    }
+}
+
+impl fmt::Display for SignOnlyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Blockhash: {}", self.blockhash)?;
+        writeln!(f, "Signed by: {}", self.signers.join(", "))?;
+        if !self.missing_signers.is_empty() {
+            writeln!(f, "Missing signatures: {}", self.missing_signers.join(", "))?;
+        }
+        write!(f, "Transaction: {}", self.transaction)
+    }
+}
+
+#[derive(Serialize)]
+pub struct SendTxReport {
+    pub signature: String,
+    pub explorer: String,
+}
+
+impl fmt::Display for SendTxReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Transaction signature: {}", self.signature)?;
+        write!(f, "Explorer: {}", self.explorer)
+    }
+}
+
+pub fn encode_transaction(tx: &Transaction) -> Result<String> {
+    Ok(BASE64.encode(bincode::serialize(tx)?))
+}
+
+pub fn decode_transaction(encoded: &str) -> Result<Transaction> {
+    let bytes = BASE64
+        .decode(encoded.trim())
+        .context("Transaction is not valid base64")?;
+    bincode::deserialize(&bytes).context("Failed to deserialize transaction")
+}
+
+/// Required signers of `tx` and whether each has signed yet.
+fn signer_statuses(tx: &Transaction) -> Vec<(Pubkey, bool)> {
+    let num_signers = tx.message.header.num_required_signatures as usize;
+    tx.message.account_keys[..num_signers]
+        .iter()
+        .zip(&tx.signatures)
+        .map(|(key, signature)| (*key, *signature != Signature::default()))
+        .collect()
+}
+
+/// Signs with every keypair in `signers` that the message requires,
+/// keeping signatures that are already present.
+pub fn partial_sign(tx: &mut Transaction, signers: &[&Keypair], blockhash: Hash) -> Result<()> {
+    let required: Vec<Pubkey> = signer_statuses(tx)
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    let mut keypairs: Vec<&Keypair> = Vec::new();
+    for signer in signers {
+        let key = signer.pubkey();
+        if required.contains(&key) && !keypairs.iter().any(|k| k.pubkey() == key) {
+            keypairs.push(signer);
+        }
+    }
+    tx.try_partial_sign(&keypairs, blockhash)?;
+    Ok(())
+}
+
+pub fn ensure_fully_signed(tx: &Transaction) -> Result<()> {
+    let missing: Vec<String> = signer_statuses(tx)
+        .into_iter()
+        .filter(|(_, is_signed)| !is_signed)
+        .map(|(key, _)| key.to_string())
+        .collect();
+    if !missing.is_empty() {
+        bail!(
+            "Transaction is missing signatures from {}; pass them with --signer or collect them with --sign-only and `tx sign`",
+            missing.join(", ")
+        );
+    }
+    Ok(())
+}
+
+pub fn sign(context: &CliContext, args: TransactionArgs) -> Result<()> {
+    let mut tx = decode_transaction(&args.transaction)?;
+    let blockhash = tx.message.recent_blockhash;
+    partial_sign(&mut tx, &context.available_signers(), blockhash)?;
+    context.output.print(&SignOnlyReport::new(&tx)?)
+}
+
+/// Runs without a payer, so the online machine needs no keys.
+pub fn send(
+    client: &RpcClient,
+    cluster: &Cluster,
+    output: OutputFormat,
+    args: TransactionArgs,
+) -> Result<()> {
+    let tx = decode_transaction(&args.transaction)?;
+    ensure_fully_signed(&tx)?;
+    tx.verify()
+        .context("Transaction has an invalid signature")?;
+
+    let signature = client.send_and_confirm_transaction(&tx)?;
+    output.print(&SendTxReport {
+        signature: signature.to_string(),
+        explorer: cluster.explorer_tx_url(signature),
+    })
+}
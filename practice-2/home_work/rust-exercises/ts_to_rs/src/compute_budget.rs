@@ -0,0 +1,113 @@
+use anyhow::{Context, Result, bail};
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, instruction::Instruction, message::Message,
+    pubkey::Pubkey, transaction::Transaction,
+};
+
+use crate::CliContext;
+
+/// The most compute units a transaction may request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Builds the ComputeBudget instructions requested on the command line, or
+/// none if no limit or priority fee was asked for.
+pub fn compute_budget_instructions(
+    context: &CliContext,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+) -> Result<Vec<Instruction>> {
+    let tx_args = &context.tx;
+    let priority_fee = match tx_args.priority_fee {
+        Some(priority_fee) => Some(priority_fee),
+        None if tx_args.auto_priority_fee => {
+            Some(recent_priority_fee(context, instructions, payer)?)
+        }
+        None => None,
+    };
+    let price_instruction = priority_fee.map(ComputeBudgetInstruction::set_compute_unit_price);
+
+    let compute_unit_limit = match tx_args.compute_unit_limit {
+        Some(limit) => Some(limit),
+        None if tx_args.simulate => {
+            // Simulate with the budget instructions in place, they cost
+            // compute units too.
+            let mut simulated = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+                MAX_COMPUTE_UNIT_LIMIT,
+            )];
+            simulated.extend(price_instruction.clone());
+            simulated.extend_from_slice(instructions);
+            let units = simulate_compute_units(context, &simulated, payer)?;
+            Some(with_margin(units, tx_args.compute_unit_margin))
+        }
+        None => None,
+    };
+
+    let mut budget_instructions = Vec::new();
+    if let Some(limit) = compute_unit_limit {
+        budget_instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+    }
+    budget_instructions.extend(price_instruction);
+    Ok(budget_instructions)
+}
+
+fn with_margin(units: u64, margin_percent: u32) -> u32 {
+    let limit = units.saturating_mul(100 + margin_percent as u64) / 100;
+    limit.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+}
+
+fn simulate_compute_units(
+    context: &CliContext,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+) -> Result<u64> {
+    let tx = Transaction::new_unsigned(Message::new(instructions, Some(payer)));
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(context.commitment),
+        ..RpcSimulateTransactionConfig::default()
+    };
+    let result = context
+        .client
+        .simulate_transaction_with_config(&tx, config)
+        .context("Failed to simulate transaction")?
+        .value;
+    if let Some(err) = result.err {
+        bail!(
+            "Simulation failed: {}\n{}",
+            err,
+            result.logs.unwrap_or_default().join("\n")
+        );
+    }
+    result
+        .units_consumed
+        .context("Simulation did not report the compute units consumed")
+}
+
+/// Median of the fees recently paid to write to the same accounts, in
+/// micro-lamports per compute unit.
+fn recent_priority_fee(
+    context: &CliContext,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+) -> Result<u64> {
+    let mut writable_accounts = vec![*payer];
+    for account in instructions.iter().flat_map(|ix| &ix.accounts) {
+        if account.is_writable && !writable_accounts.contains(&account.pubkey) {
+            writable_accounts.push(account.pubkey);
+        }
+    }
+    let mut fees: Vec<u64> = context
+        .client
+        .get_recent_prioritization_fees(&writable_accounts)
+        .context("Failed to fetch recent prioritization fees")?
+        .into_iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect();
+    if fees.is_empty() {
+        return Ok(0);
+    }
+    fees.sort_unstable();
+    Ok(fees[fees.len() / 2])
+}
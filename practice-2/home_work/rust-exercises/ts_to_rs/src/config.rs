@@ -0,0 +1,191 @@
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::{env, fmt::Display, fs, path::PathBuf};
+
+use crate::cli::Cli;
+
+const DEFAULT_RPC_URL: &str = "https://api.devnet.solana.com";
+const RPC_URL_ENV: &str = "SOLANA_RPC_URL";
+const COMMITMENT_ENV: &str = "SOLANA_COMMITMENT";
+
+/// The subset of the Solana CLI `config.yml` we care about.
+#[derive(Default, Deserialize)]
+struct SolanaCliConfig {
+    json_rpc_url: Option<String>,
+    commitment: Option<String>,
+    keypair_path: Option<String>,
+}
+
+impl SolanaCliConfig {
+    fn default_path() -> Option<PathBuf> {
+        Some(home_dir()?.join(".config/solana/cli/config.yml"))
+    }
+
+    /// Reads `path`, or the default location. A missing default file is not
+    /// an error, an explicitly requested one is.
+    fn load(path: Option<&PathBuf>) -> Result<Self> {
+        let (path, required) = match path {
+            Some(path) => (path.clone(), true),
+            None => match Self::default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+        if !required && !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read Solana config {}", path.display()))?;
+        serde_yaml::from_str(&contents)
+            .with_context(|| format!("Failed to parse Solana config {}", path.display()))
+    }
+}
+
+pub fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(PathBuf::from)
+}
+
+/// Expands a leading `~/` the way the Solana CLI does for keypair paths.
+pub fn expand_tilde(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Cluster {
+    Mainnet,
+    Devnet,
+    Testnet,
+    Localnet(String),
+    Custom(String),
+}
+
+impl Cluster {
+    /// Accepts the same monikers as `solana config set --url`, or any RPC URL.
+    pub fn parse(url_or_moniker: &str) -> Self {
+        match url_or_moniker {
+            "m" | "mainnet" | "mainnet-beta" => return Cluster::Mainnet,
+            "d" | "devnet" => return Cluster::Devnet,
+            "t" | "testnet" => return Cluster::Testnet,
+            "l" | "localhost" | "localnet" => {
+                return Cluster::Localnet("http://localhost:8899".to_string());
+            }
+            _ => {}
+        }
+        let url = url_or_moniker.trim_end_matches('/');
+        if url.contains("api.mainnet-beta.solana.com") {
+            Cluster::Mainnet
+        } else if url.contains("api.devnet.solana.com") {
+            Cluster::Devnet
+        } else if url.contains("api.testnet.solana.com") {
+            Cluster::Testnet
+        } else if url.contains("localhost") || url.contains("127.0.0.1") {
+            Cluster::Localnet(url.to_string())
+        } else {
+            Cluster::Custom(url.to_string())
+        }
+    }
+
+    pub fn rpc_url(&self) -> &str {
+        match self {
+            Cluster::Mainnet => "https://api.mainnet-beta.solana.com",
+            Cluster::Devnet => "https://api.devnet.solana.com",
+            Cluster::Testnet => "https://api.testnet.solana.com",
+            Cluster::Localnet(url) | Cluster::Custom(url) => url,
+        }
+    }
+
+    fn explorer_query(&self) -> String {
+        match self {
+            Cluster::Mainnet => String::new(),
+            Cluster::Devnet => "?cluster=devnet".to_string(),
+            Cluster::Testnet => "?cluster=testnet".to_string(),
+            Cluster::Localnet(url) | Cluster::Custom(url) => {
+                format!("?cluster=custom&customUrl={}", url_encode(url))
+            }
+        }
+    }
+
+    pub fn explorer_address_url(&self, address: impl Display) -> String {
+        format!(
+            "https://explorer.solana.com/address/{}{}",
+            address,
+            self.explorer_query()
+        )
+    }
+
+    pub fn explorer_tx_url(&self, signature: impl Display) -> String {
+        format!(
+            "https://explorer.solana.com/tx/{}{}",
+            signature,
+            self.explorer_query()
+        )
+    }
+}
+
+fn url_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn parse_commitment(value: &str) -> Result<CommitmentConfig> {
+    match value {
+        "processed" => Ok(CommitmentConfig::processed()),
+        "confirmed" => Ok(CommitmentConfig::confirmed()),
+        "finalized" => Ok(CommitmentConfig::finalized()),
+        other => bail!(
+            "Unknown commitment '{}', expected processed, confirmed or finalized",
+            other
+        ),
+    }
+}
+
+/// Connection settings, resolved in order from command line flags,
+/// environment variables (including `.env`), the Solana CLI config file and
+/// finally the devnet defaults.
+pub struct Config {
+    pub cluster: Cluster,
+    pub commitment: CommitmentConfig,
+    /// `keypair_path` from the Solana CLI config, used when no signer is
+    /// given explicitly.
+    pub keypair_path: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn resolve(cli: &Cli) -> Result<Self> {
+        let solana_config = SolanaCliConfig::load(cli.config.as_ref())?;
+
+        let url = cli
+            .url
+            .clone()
+            .or_else(|| env::var(RPC_URL_ENV).ok())
+            .or(solana_config.json_rpc_url)
+            .unwrap_or_else(|| DEFAULT_RPC_URL.to_string());
+
+        let commitment = match cli
+            .commitment
+            .clone()
+            .or_else(|| env::var(COMMITMENT_ENV).ok())
+            .or(solana_config.commitment)
+        {
+            Some(commitment) => parse_commitment(&commitment)?,
+            None => CommitmentConfig::confirmed(),
+        };
+
+        Ok(Config {
+            cluster: Cluster::parse(&url),
+            commitment,
+            keypair_path: solana_config.keypair_path.as_deref().map(expand_tilde),
+        })
+    }
+}
@@ -0,0 +1,79 @@
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Where a sent transaction ended up.
+#[derive(Serialize)]
+pub struct Confirmation {
+    pub slot: u64,
+    pub fee: u64,
+    pub status: String,
+    pub finalized: bool,
+}
+
+/// Polls the signature status until it is at least confirmed, or finalized
+/// when `wait_finalized` is set, then reads the slot and fee of the
+/// transaction. `get_transaction` doesn't serve merely processed
+/// transactions, so a `processed` send commitment still waits here.
+pub fn track(
+    client: &RpcClient,
+    signature: &Signature,
+    wait_finalized: bool,
+) -> Result<Confirmation> {
+    let started = Instant::now();
+    let (target, target_name) = if wait_finalized {
+        (CommitmentConfig::finalized(), "finalized")
+    } else {
+        (CommitmentConfig::confirmed(), "confirmed")
+    };
+    let status = loop {
+        let status = client
+            .get_signature_statuses(&[*signature])
+            .context("Failed to fetch the signature status")?
+            .value
+            .remove(0);
+        if let Some(status) = status {
+            if let Some(err) = &status.err {
+                bail!("Transaction {} failed: {}", signature, err);
+            }
+            if status.satisfies_commitment(target) {
+                break status;
+            }
+        }
+        if started.elapsed() > CONFIRMATION_TIMEOUT {
+            bail!("Transaction {} was not {} in time", signature, target_name);
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let transaction = client
+        .get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: None,
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .context("Failed to fetch the confirmed transaction")?;
+    let fee = transaction
+        .transaction
+        .meta
+        .map(|meta| meta.fee)
+        .context("Confirmed transaction has no status meta")?;
+
+    Ok(Confirmation {
+        slot: transaction.slot,
+        fee,
+        status: format!("{:?}", status.confirmation_status()).to_lowercase(),
+        finalized: status.satisfies_commitment(CommitmentConfig::finalized()),
+    })
+}
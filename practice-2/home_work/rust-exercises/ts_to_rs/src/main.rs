@@ -0,0 +1,106 @@
+mod amount;
+mod cli;
+mod commands;
+mod compute_budget;
+mod config;
+mod confirmation;
+mod metadata_json;
+mod output;
+mod signer;
+
+use anyhow::Result;
+use clap::Parser;
+use dotenvy::dotenv;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Keypair};
+
+use cli::{Cli, Command, MetadataCommand, SolCommand, TokenCommand, TxArgs, TxCommand};
+use config::{Cluster, Config};
+use output::OutputFormat;
+
+/// Everything a subcommand needs to build, send and report a transaction.
+pub struct CliContext {
+    pub client: RpcClient,
+    pub cluster: Cluster,
+    pub commitment: CommitmentConfig,
+    pub payer: Keypair,
+    /// Keypairs passed with `--signer`.
+    pub signers: Vec<Keypair>,
+    pub nonce_authority: Option<Keypair>,
+    pub tx: TxArgs,
+    pub output: OutputFormat,
+}
+
+impl CliContext {
+    pub fn nonce_authority(&self) -> &Keypair {
+        self.nonce_authority.as_ref().unwrap_or(&self.payer)
+    }
+
+    /// Every keypair loaded for this run, the payer first.
+    pub fn available_signers(&self) -> Vec<&Keypair> {
+        let mut signers = vec![&self.payer];
+        signers.extend(&self.signers);
+        signers.extend(&self.nonce_authority);
+        signers
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    let cli = Cli::parse();
+
+    let config = Config::resolve(&cli)?;
+    let client =
+        RpcClient::new_with_commitment(config.cluster.rpc_url().to_string(), config.commitment);
+
+    if let Command::Tx(TxCommand::Send(args)) = cli.command {
+        return commands::tx::send(&client, &config.cluster, cli.output, args);
+    }
+
+    let context = CliContext {
+        client,
+        cluster: config.cluster.clone(),
+        commitment: config.commitment,
+        payer: signer::load_payer(&cli, &config)?,
+        signers: cli
+            .tx
+            .signers
+            .iter()
+            .map(|source| signer::load_signer(source, &cli.derivation_path))
+            .collect::<Result<_>>()?,
+        nonce_authority: cli
+            .tx
+            .nonce_authority
+            .as_deref()
+            .map(|source| signer::load_signer(source, &cli.derivation_path))
+            .transpose()?,
+        tx: cli.tx,
+        output: cli.output,
+    };
+
+    match cli.command {
+        Command::Sol(SolCommand::Send(args)) => commands::sol::send(&context, args),
+        Command::Token(TokenCommand::CreateMint(args)) => {
+            commands::token::create_mint(&context, args)
+        }
+        Command::Token(TokenCommand::Mint(args)) => commands::token::mint(&context, args),
+        Command::Token(TokenCommand::CreateAta(args)) => {
+            commands::token::create_ata(&context, args)
+        }
+        Command::Token(TokenCommand::Distribute(args)) => {
+            commands::distribute::distribute(&context, args)
+        }
+        Command::Token(TokenCommand::Metadata(MetadataCommand::Create(args))) => {
+            commands::metadata::create(&context, args)
+        }
+        Command::Token(TokenCommand::Metadata(MetadataCommand::Show(args))) => {
+            commands::metadata::show(&context, args)
+        }
+        Command::Token(TokenCommand::Metadata(MetadataCommand::Update(args))) => {
+            commands::metadata::update(&context, args)
+        }
+        Command::Tx(TxCommand::Sign(args)) => commands::tx::sign(&context, args),
+        Command::Tx(TxCommand::Send(_)) => unreachable!("handled before loading signers"),
+    }
+}
@@ -0,0 +1,196 @@
+use anyhow::{Context, Result, bail};
+use serde_json::{Map, Value};
+
+/// Fetches the off-chain JSON at `uri` and checks it against the Metaplex
+/// fungible token standard. `name` and `symbol` must match the on-chain
+/// values they will be shown next to.
+pub fn validate_uri(uri: &str, name: &str, symbol: &str) -> Result<()> {
+    let json: Value = ureq::get(uri)
+        .call()
+        .with_context(|| format!("Failed to fetch metadata JSON from {}", uri))?
+        .into_json()
+        .with_context(|| format!("{} did not return JSON", uri))?;
+
+    let problems = schema_problems(&json, name, symbol);
+    if !problems.is_empty() {
+        bail!(
+            "Invalid metadata JSON at {}:\n  - {}",
+            uri,
+            problems.join("\n  - ")
+        );
+    }
+    Ok(())
+}
+
+fn schema_problems(json: &Value, name: &str, symbol: &str) -> Vec<String> {
+    let Some(object) = json.as_object() else {
+        return vec!["expected a JSON object".to_string()];
+    };
+    let mut problems = Vec::new();
+
+    for (field, on_chain) in [("name", name), ("symbol", symbol)] {
+        match object.get(field) {
+            Some(Value::String(value)) if value == on_chain => {}
+            Some(Value::String(value)) => problems.push(format!(
+                "{} is '{}' but the on-chain {} is '{}'",
+                field, value, field, on_chain
+            )),
+            Some(_) => problems.push(format!("{} must be a string", field)),
+            None => problems.push(format!("{} is missing", field)),
+        }
+    }
+    for field in ["description", "image", "animation_url", "external_url"] {
+        check_optional_string(object, field, &mut problems);
+    }
+
+    if let Some(attributes) = object.get("attributes") {
+        match attributes.as_array() {
+            Some(attributes) => {
+                for (index, attribute) in attributes.iter().enumerate() {
+                    let has_fields = attribute
+                        .as_object()
+                        .is_some_and(|a| a.contains_key("trait_type") && a.contains_key("value"));
+                    if !has_fields {
+                        problems.push(format!("attributes[{}] needs trait_type and value", index));
+                    }
+                }
+            }
+            None => problems.push("attributes must be an array".to_string()),
+        }
+    }
+
+    if let Some(properties) = object.get("properties") {
+        match properties.as_object() {
+            Some(properties) => check_files(properties, &mut problems),
+            None => problems.push("properties must be an object".to_string()),
+        }
+    }
+    problems
+}
+
+fn check_optional_string(object: &Map<String, Value>, field: &str, problems: &mut Vec<String>) {
+    if object.get(field).is_some_and(|value| !value.is_string()) {
+        problems.push(format!("{} must be a string", field));
+    }
+}
+
+fn check_files(properties: &Map<String, Value>, problems: &mut Vec<String>) {
+    let Some(files) = properties.get("files") else {
+        return;
+    };
+    let Some(files) = files.as_array() else {
+        problems.push("properties.files must be an array".to_string());
+        return;
+    };
+    for (index, file) in files.iter().enumerate() {
+        let valid = file.as_object().is_some_and(|file| {
+            file.get("uri").is_some_and(Value::is_string)
+                && file.get("type").is_some_and(Value::is_string)
+        });
+        if !valid {
+            problems.push(format!(
+                "properties.files[{}] needs uri and type strings",
+                index
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn problems(json: Value) -> Vec<String> {
+        schema_problems(&json, "Rust Token", "RT")
+    }
+
+    #[test]
+    fn accepts_a_complete_fungible_token_json() {
+        let json = json!({
+            "name": "Rust Token",
+            "symbol": "RT",
+            "description": "A token",
+            "image": "https://example.com/rt.png",
+            "attributes": [{ "trait_type": "speed", "value": "fast" }],
+            "properties": {
+                "files": [{ "uri": "https://example.com/rt.png", "type": "image/png" }]
+            }
+        });
+        assert!(problems(json).is_empty());
+    }
+
+    #[test]
+    fn accepts_only_name_and_symbol() {
+        assert!(problems(json!({ "name": "Rust Token", "symbol": "RT" })).is_empty());
+    }
+
+    #[test]
+    fn rejects_anything_but_an_object() {
+        assert_eq!(problems(json!(["Rust Token"])), ["expected a JSON object"]);
+    }
+
+    #[test]
+    fn reports_missing_and_mismatched_name_and_symbol() {
+        assert_eq!(
+            problems(json!({ "name": "Other Token" })),
+            [
+                "name is 'Other Token' but the on-chain name is 'Rust Token'",
+                "symbol is missing",
+            ]
+        );
+        assert_eq!(
+            problems(json!({ "name": 1, "symbol": "RT" })),
+            ["name must be a string"]
+        );
+    }
+
+    #[test]
+    fn reports_optional_fields_of_the_wrong_type() {
+        let json = json!({
+            "name": "Rust Token",
+            "symbol": "RT",
+            "description": 1,
+            "external_url": null,
+        });
+        assert_eq!(
+            problems(json),
+            [
+                "description must be a string",
+                "external_url must be a string"
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_malformed_attributes() {
+        let json = json!({
+            "name": "Rust Token",
+            "symbol": "RT",
+            "attributes": [{ "trait_type": "speed", "value": "fast" }, { "value": "slow" }],
+        });
+        assert_eq!(problems(json), ["attributes[1] needs trait_type and value"]);
+
+        let json = json!({ "name": "Rust Token", "symbol": "RT", "attributes": {} });
+        assert_eq!(problems(json), ["attributes must be an array"]);
+    }
+
+    #[test]
+    fn reports_malformed_files() {
+        let json = json!({
+            "name": "Rust Token",
+            "symbol": "RT",
+            "properties": { "files": [{ "uri": "https://example.com/rt.png" }] },
+        });
+        assert_eq!(
+            problems(json),
+            ["properties.files[0] needs uri and type strings"]
+        );
+
+        let json = json!({ "name": "Rust Token", "symbol": "RT", "properties": { "files": {} } });
+        assert_eq!(problems(json), ["properties.files must be an array"]);
+
+        let json = json!({ "name": "Rust Token", "symbol": "RT", "properties": [] });
+        assert_eq!(problems(json), ["properties must be an object"]);
+    }
+}
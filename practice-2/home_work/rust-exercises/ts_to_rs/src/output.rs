@@ -0,0 +1,21 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fmt::Display;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    /// Prints a command result, either human-readable or as one JSON object.
+    pub fn print<T: Serialize + Display>(self, result: &T) -> Result<()> {
+        match self {
+            OutputFormat::Text => println!("{}", result),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(result)?),
+        }
+        Ok(())
+    }
+}
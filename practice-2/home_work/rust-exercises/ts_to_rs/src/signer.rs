@@ -0,0 +1,192 @@
+use anyhow::{Context, Result, anyhow, bail};
+use bip39::Mnemonic;
+use solana_sdk::{
+    derivation_path::DerivationPath, signature::Keypair,
+    signer::keypair::keypair_from_seed_and_derivation_path,
+};
+use std::{env, fs, path::Path};
+
+use crate::{
+    cli::Cli,
+    config::{Config, expand_tilde, home_dir},
+};
+
+const SECRET_KEY_ENV: &str = "SECRET_KEY";
+const SEED_PASSPHRASE_ENV: &str = "SEED_PASSPHRASE";
+
+/// Loads the fee payer from, in order: `--keypair`, `SECRET_KEY` (usually from
+/// `.env`), the Solana CLI config `keypair_path` and `~/.config/solana/id.json`.
+pub fn load_payer(cli: &Cli, config: &Config) -> Result<Keypair> {
+    if let Some(source) = &cli.keypair {
+        return load_signer(source, &cli.derivation_path)
+            .context("Failed to load the --keypair signer");
+    }
+    if let Ok(secret) = env::var(SECRET_KEY_ENV) {
+        return parse_secret(&secret, &cli.derivation_path)
+            .with_context(|| format!("Failed to load the {} signer", SECRET_KEY_ENV));
+    }
+    if let Some(path) = &config.keypair_path {
+        return read_keypair_json_file(path);
+    }
+    match home_dir().map(|home| home.join(".config/solana/id.json")) {
+        Some(path) if path.exists() => read_keypair_json_file(&path),
+        _ => bail!(
+            "No signer found: pass --keypair, set {} or create ~/.config/solana/id.json",
+            SECRET_KEY_ENV
+        ),
+    }
+}
+
+/// `source` is either a path to a keypair file or an inline secret.
+pub fn load_signer(source: &str, derivation_path: &str) -> Result<Keypair> {
+    let path = expand_tilde(source);
+    if path.is_file() {
+        return read_keypair_json_file(&path);
+    }
+    parse_secret(source, derivation_path)
+}
+
+fn read_keypair_json_file(path: &Path) -> Result<Keypair> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read keypair file {}", path.display()))?;
+    keypair_from_json(&contents).with_context(|| format!("Invalid keypair file {}", path.display()))
+}
+
+/// Accepts a JSON byte array, a base58 encoded secret key or a seed phrase.
+fn parse_secret(secret: &str, derivation_path: &str) -> Result<Keypair> {
+    let secret = secret.trim();
+    if secret.starts_with('[') {
+        keypair_from_json(secret)
+    } else if secret.split_whitespace().count() > 1 {
+        keypair_from_seed_phrase(secret, derivation_path)
+    } else {
+        keypair_from_base58(secret)
+    }
+}
+
+fn keypair_from_json(json: &str) -> Result<Keypair> {
+    let bytes: Vec<u8> =
+        serde_json::from_str(json).context("Failed to parse secret key as a JSON byte array")?;
+    keypair_from_bytes(&bytes)
+}
+
+fn keypair_from_base58(secret: &str) -> Result<Keypair> {
+    let bytes = bs58::decode(secret)
+        .into_vec()
+        .context("Secret key is neither a keypair file, JSON, base58 nor a seed phrase")?;
+    keypair_from_bytes(&bytes)
+}
+
+fn keypair_from_bytes(bytes: &[u8]) -> Result<Keypair> {
+    if bytes.len() != 64 {
+        bail!("Secret key must be 64 bytes, got {}", bytes.len());
+    }
+    Keypair::from_bytes(bytes).map_err(|e| anyhow!("Failed to create keypair: {}", e))
+}
+
+fn keypair_from_seed_phrase(phrase: &str, derivation_path: &str) -> Result<Keypair> {
+    let mnemonic = Mnemonic::parse_normalized(phrase).context("Invalid BIP39 seed phrase")?;
+    let passphrase = env::var(SEED_PASSPHRASE_ENV).unwrap_or_default();
+    let seed = mnemonic.to_seed(&passphrase);
+
+    let derivation_path = DerivationPath::from_absolute_path_str(derivation_path)
+        .map_err(|e| anyhow!("Invalid derivation path '{}': {}", derivation_path, e))?;
+    keypair_from_seed_and_derivation_path(&seed, Some(derivation_path))
+        .map_err(|e| anyhow!("Failed to derive keypair from seed phrase: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signer::Signer;
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon \
+                          abandon abandon abandon about";
+    const DEFAULT_PATH: &str = "m/44'/501'/0'/0'";
+
+    fn error(result: Result<Keypair>) -> String {
+        format!("{:#}", result.err().unwrap())
+    }
+
+    #[test]
+    fn derives_known_keypairs_from_a_seed_phrase() {
+        let keypair = parse_secret(PHRASE, DEFAULT_PATH).unwrap();
+        assert_eq!(
+            keypair.pubkey().to_string(),
+            "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk"
+        );
+
+        let keypair = parse_secret(PHRASE, "m/44'/501'/1'/0'").unwrap();
+        assert_eq!(
+            keypair.pubkey().to_string(),
+            "Hh8QwFUA6MtVu1qAoq12ucvFHNwCcVTV7hpWjeY1Hztb"
+        );
+    }
+
+    #[test]
+    fn parses_json_and_base58_secret_keys() {
+        let keypair = Keypair::new();
+        let bytes = keypair.to_bytes();
+
+        let json = serde_json::to_string(&bytes.to_vec()).unwrap();
+        assert_eq!(
+            parse_secret(&json, DEFAULT_PATH).unwrap().pubkey(),
+            keypair.pubkey()
+        );
+
+        let base58 = bs58::encode(bytes).into_string();
+        let padded = format!("  {}\n", base58);
+        assert_eq!(
+            parse_secret(&padded, DEFAULT_PATH).unwrap().pubkey(),
+            keypair.pubkey()
+        );
+    }
+
+    #[test]
+    fn loads_keypair_files() {
+        let keypair = Keypair::new();
+        let path = env::temp_dir().join(format!("ts_to_rs-signer-{}.json", std::process::id()));
+        fs::write(
+            &path,
+            serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap(),
+        )
+        .unwrap();
+
+        let loaded = load_signer(path.to_str().unwrap(), DEFAULT_PATH);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap().pubkey(), keypair.pubkey());
+    }
+
+    #[test]
+    fn rejects_malformed_secrets() {
+        assert!(error(parse_secret("[1, 2, 3]", DEFAULT_PATH)).contains("must be 64 bytes, got 3"));
+        assert!(error(parse_secret("[1, 2,", DEFAULT_PATH)).contains("JSON byte array"));
+
+        let short = bs58::encode([7u8; 32]).into_string();
+        assert!(error(parse_secret(&short, DEFAULT_PATH)).contains("must be 64 bytes, got 32"));
+        assert!(error(parse_secret("not-base58-0OIl", DEFAULT_PATH)).contains("neither"));
+        // Missing files fall through to being parsed as an inline secret.
+        assert!(error(load_signer("/nonexistent/id.json", DEFAULT_PATH)).contains("neither"));
+    }
+
+    #[test]
+    fn rejects_malformed_seed_phrases_and_paths() {
+        assert!(
+            error(parse_secret("abandon abandon abandon", DEFAULT_PATH))
+                .contains("Invalid BIP39 seed phrase")
+        );
+        // Twelve valid words, but the checksum doesn't match.
+        let bad_checksum = ["abandon"; 12].join(" ");
+        assert!(
+            error(parse_secret(&bad_checksum, DEFAULT_PATH)).contains("Invalid BIP39 seed phrase")
+        );
+        assert!(
+            error(parse_secret("correct horse battery staple", DEFAULT_PATH))
+                .contains("Invalid BIP39 seed phrase")
+        );
+
+        for path in ["m/44'/abc'", "44'/501'", ""] {
+            assert!(error(parse_secret(PHRASE, path)).contains("Invalid derivation path"));
+        }
+    }
+}
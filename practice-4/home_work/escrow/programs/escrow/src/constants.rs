@@ -3,3 +3,7 @@ use anchor_lang::prelude::*;
 #[constant]
 pub const SEED: &str = "anchor";
 pub const ANCHOR_DISCRIMINATOR: usize = 8;
+
+/// Size of a Token-2022 ATA with the `ImmutableOwner` extension, which also
+/// covers a classic SPL token account (165 bytes).
+pub const ASSOCIATED_TOKEN_ACCOUNT_SPACE: usize = 170;
@@ -4,4 +4,38 @@ use anchor_lang::prelude::*;
 pub enum ErrorCode {
     #[msg("Custom error message")]
     CustomError,
+    #[msg("Offer no longer asks for the expected amount of token B")]
+    OfferTermsChanged,
+    #[msg("Vault holds less token A than the taker is willing to receive")]
+    SlippageExceeded,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Vesting cliff must be within a positive duration")]
+    InvalidVestingSchedule,
+    #[msg("Offer is vested, vesting accounts must be provided")]
+    VestingAccountsMissing,
+    #[msg("Offer is not vested, vesting accounts must not be provided")]
+    UnexpectedVestingAccounts,
+    #[msg("Nothing to claim yet")]
+    NothingToClaim,
+    #[msg("Offer has already been accepted or is under dispute")]
+    OfferNotOpen,
+    #[msg("Offer has not been accepted by a taker")]
+    OfferNotAccepted,
+    #[msg("Offer is not under dispute")]
+    OfferNotDisputed,
+    #[msg("Offer has no arbiter or the signer is not its arbiter")]
+    NotArbiter,
+    #[msg("Only offers with an arbiter can be accepted, take it instead")]
+    NoArbiter,
+    #[msg("Offers with an arbiter must be accepted, not taken")]
+    OfferHasArbiter,
+    #[msg("Account is not the taker who accepted the offer")]
+    NotTaker,
+    #[msg("Vested offers cannot have an arbiter")]
+    VestingWithArbiter,
+    #[msg("Split gives the maker more than the vault holds")]
+    InvalidSplit,
+    #[msg("Explicit offer ID must equal the maker's next offer ID")]
+    UnexpectedOfferId,
 }
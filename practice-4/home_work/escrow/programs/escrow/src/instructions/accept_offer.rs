@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{error::ErrorCode, Offer, OfferStatus};
+
+#[derive(Accounts)]
+pub struct AcceptOffer<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_b: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", offer.maker.as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump = offer.bump,
+        has_one = token_mint_b,
+        constraint = offer.arbiter.is_some() @ ErrorCode::NoArbiter,
+        constraint = offer.status == OfferStatus::Open @ ErrorCode::OfferNotOpen,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    /// Holds the taker's token B until the offer is completed or the arbiter
+    /// resolves a dispute.
+    #[account(
+        init,
+        payer = taker,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program,
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Binds the taker to an arbitrated offer and locks their token B payment in
+/// the payment vault. Nothing reaches the maker until the taker calls
+/// `complete_offer` or the arbiter resolves a dispute.
+pub fn accept_offer(context: Context<AcceptOffer>, expected_token_b_amount: u64) -> Result<()> {
+    let amount = context.accounts.offer.token_b_wanted_amount;
+    require_eq!(
+        amount,
+        expected_token_b_amount,
+        ErrorCode::OfferTermsChanged
+    );
+
+    let transfer_accounts = TransferChecked {
+        from: context.accounts.taker_token_account_b.to_account_info(),
+        mint: context.accounts.token_mint_b.to_account_info(),
+        to: context.accounts.payment_vault.to_account_info(),
+        authority: context.accounts.taker.to_account_info(),
+    };
+    let cpi_context = CpiContext::new(
+        context.accounts.token_program.to_account_info(),
+        transfer_accounts,
+    );
+    transfer_checked(cpi_context, amount, context.accounts.token_mint_b.decimals)?;
+
+    let offer = &mut context.accounts.offer;
+    offer.taker = Some(context.accounts.taker.key());
+    offer.status = OfferStatus::Accepted;
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::{error::ErrorCode, Vesting};
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"vesting", vesting.offer.as_ref()],
+        bump = vesting.bump,
+        has_one = taker,
+        has_one = token_mint_a,
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program,
+    )]
+    pub vesting_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_account_a: InterfaceAccount<'info, TokenAccount>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Releases whatever has unlocked since the last claim. Once everything is
+/// claimed the vesting vault and account are closed back to the taker.
+pub fn claim_vested(context: Context<ClaimVested>) -> Result<()> {
+    let vesting = &context.accounts.vesting;
+    let now = Clock::get()?.unix_timestamp;
+    let claimable = vesting.unlocked_amount(now) - vesting.claimed_amount;
+    require_gt!(claimable, 0, ErrorCode::NothingToClaim);
+
+    let offer_key = vesting.offer;
+    let seeds = &[b"vesting", offer_key.as_ref(), &[vesting.bump]];
+    let signer = &[&seeds[..]];
+
+    let transfer_accounts = TransferChecked {
+        from: context.accounts.vesting_vault.to_account_info(),
+        mint: context.accounts.token_mint_a.to_account_info(),
+        to: context.accounts.taker_token_account_a.to_account_info(),
+        authority: context.accounts.vesting.to_account_info(),
+    };
+    let cpi_context = CpiContext::new_with_signer(
+        context.accounts.token_program.to_account_info(),
+        transfer_accounts,
+        signer,
+    );
+    transfer_checked(
+        cpi_context,
+        claimable,
+        context.accounts.token_mint_a.decimals,
+    )?;
+
+    let vesting = &mut context.accounts.vesting;
+    vesting.claimed_amount += claimable;
+    if vesting.claimed_amount < vesting.total_amount {
+        return Ok(());
+    }
+
+    let close_accounts = CloseAccount {
+        account: context.accounts.vesting_vault.to_account_info(),
+        destination: context.accounts.taker.to_account_info(),
+        authority: context.accounts.vesting.to_account_info(),
+    };
+    let cpi_context = CpiContext::new_with_signer(
+        context.accounts.token_program.to_account_info(),
+        close_accounts,
+        signer,
+    );
+    close_account(cpi_context)?;
+
+    context
+        .accounts
+        .vesting
+        .close(context.accounts.taker.to_account_info())
+}
@@ -7,7 +7,7 @@ use anchor_spl::{
     },
 };
 
-use crate::Offer;
+use crate::{error::ErrorCode, MakerState, Offer, OfferStatus};
 
 #[derive(Accounts)]
 pub struct CloseOffer<'info> {
@@ -20,8 +20,16 @@ pub struct CloseOffer<'info> {
         seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
         bump = offer.bump,
         has_one = maker,
+        constraint = offer.status == OfferStatus::Open @ ErrorCode::OfferNotOpen,
     )]
     pub offer: Account<'info, Offer>,
+    #[account(
+        mut,
+        seeds = [b"maker_state", maker.key().as_ref()],
+        bump = maker_state.bump,
+        has_one = maker,
+    )]
+    pub maker_state: Account<'info, MakerState>,
 
     #[account(
         mut,
@@ -48,6 +56,9 @@ pub struct CloseOffer<'info> {
 }
 
 pub fn close_offer(context: Context<CloseOffer>) -> Result<()> {
+    let maker_state = &mut context.accounts.maker_state;
+    maker_state.open_offer_count = maker_state.open_offer_count.saturating_sub(1);
+
     // Transfer tokens from vault back to maker
     let transfer_accounts = TransferChecked {
         from: context.accounts.vault.to_account_info(),
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::ErrorCode,
+    instructions::shared::{
+        create_maker_token_account_b, release_offer_vault, vault_rent_recipient,
+    },
+    MakerState, Offer, OfferStatus,
+};
+
+#[derive(Accounts)]
+pub struct CompleteOffer<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        close = maker,
+        seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump = offer.bump,
+        has_one = maker,
+        has_one = token_mint_a,
+        has_one = token_mint_b,
+        constraint = offer.taker == Some(taker.key()) @ ErrorCode::NotTaker,
+        constraint = offer.status == OfferStatus::Accepted @ ErrorCode::OfferNotAccepted,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        seeds = [b"maker_state", maker.key().as_ref()],
+        bump = maker_state.bump,
+        has_one = maker,
+    )]
+    pub maker_state: Box<Account<'info, MakerState>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program,
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program,
+    )]
+    pub taker_token_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: created in the handler if missing, so the rent can be settled
+    /// according to `offer.rent_policy`.
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &maker.key(),
+            &token_mint_b.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub maker_token_account_b: UncheckedAccount<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// The taker confirms an accepted offer: token B goes to the maker, token A
+/// to the taker. The payment vault's rent goes back to the taker who paid it;
+/// the maker's token B ATA and the token A vault follow `offer.rent_policy`,
+/// as in `take_offer`.
+pub fn complete_offer(context: Context<CompleteOffer>) -> Result<()> {
+    let maker_state = &mut context.accounts.maker_state;
+    maker_state.open_offer_count = maker_state.open_offer_count.saturating_sub(1);
+
+    let accounts = &context.accounts;
+    create_maker_token_account_b(
+        &accounts.offer,
+        associated_token::Create {
+            payer: accounts.taker.to_account_info(),
+            associated_token: accounts.maker_token_account_b.to_account_info(),
+            authority: accounts.maker.to_account_info(),
+            mint: accounts.token_mint_b.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+        },
+        accounts.associated_token_program.to_account_info(),
+    )?;
+    release_offer_vault(
+        &accounts.offer,
+        &accounts.payment_vault,
+        &accounts.token_mint_b,
+        &[(
+            accounts.maker_token_account_b.to_account_info(),
+            accounts.payment_vault.amount,
+        )],
+        accounts.taker.to_account_info(),
+        &accounts.token_program,
+    )?;
+    release_offer_vault(
+        &accounts.offer,
+        &accounts.vault,
+        &accounts.token_mint_a,
+        &[(
+            accounts.taker_token_account_a.to_account_info(),
+            accounts.vault.amount,
+        )],
+        vault_rent_recipient(
+            &accounts.offer,
+            accounts.maker.to_account_info(),
+            accounts.taker.to_account_info(),
+        ),
+        &accounts.token_program,
+    )
+}
@@ -1,4 +1,7 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
 
 /// Mint - metadata for a token (decimals, supply, mint authority, etc.)
 /// TokenAccount - holds token balance for a specific user and mint
@@ -8,10 +11,13 @@ use anchor_spl::{
     token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use crate::{Offer, ANCHOR_DISCRIMINATOR};
+use crate::{
+    error::ErrorCode, MakerState, Offer, OfferStatus, RentParty, RentPolicy, VestingSchedule,
+    ANCHOR_DISCRIMINATOR, ASSOCIATED_TOKEN_ACCOUNT_SPACE,
+};
 
 #[derive(Accounts)]
-#[instruction(id: u64)]
+#[instruction(id: Option<u64>)]
 pub struct MakeOffer<'info> {
     #[account(mut)]
     pub maker: Signer<'info>,
@@ -30,11 +36,24 @@ pub struct MakeOffer<'info> {
     )]
     pub maker_token_account_a: InterfaceAccount<'info, TokenAccount>,
 
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = ANCHOR_DISCRIMINATOR + MakerState::INIT_SPACE,
+        seeds = [b"maker_state", maker.key().as_ref()],
+        bump
+    )]
+    pub maker_state: Account<'info, MakerState>,
+
     #[account(
         init,
         payer = maker,
         space = ANCHOR_DISCRIMINATOR + Offer::INIT_SPACE,
-        seeds = [b"offer", maker.key().as_ref(), id.to_le_bytes().as_ref()],
+        seeds = [
+            b"offer",
+            maker.key().as_ref(),
+            id.unwrap_or(maker_state.next_offer_id).to_le_bytes().as_ref()
+        ],
         bump
     )]
     pub offer: Account<'info, Offer>,
@@ -76,13 +95,77 @@ pub fn send_offered_tokens_to_vault(
     )
 }
 
-pub fn save_offer(context: Context<MakeOffer>, id: u64, token_b_wanted_amount: u64) -> Result<()> {
+/// When the maker agreed to pay for their own token B ATA, parks its rent on
+/// the offer so `take_offer` can reimburse the taker who creates it.
+pub fn deposit_maker_ata_rent(
+    context: &Context<MakeOffer>,
+    rent_policy: &RentPolicy,
+) -> Result<u64> {
+    if rent_policy.maker_ata_payer != RentParty::Maker {
+        return Ok(0);
+    }
+
+    let deposit = Rent::get()?.minimum_balance(ASSOCIATED_TOKEN_ACCOUNT_SPACE);
+    let cpi_context = CpiContext::new(
+        context.accounts.system_program.to_account_info(),
+        Transfer {
+            from: context.accounts.maker.to_account_info(),
+            to: context.accounts.offer.to_account_info(),
+        },
+    );
+    transfer(cpi_context, deposit)?;
+    Ok(deposit)
+}
+
+/// Resolves the offer ID (explicit or allocated from `MakerState`) and
+/// updates the maker's counters. An explicit ID must equal `next_offer_id`:
+/// it only guards against racing another offer from the same maker, so IDs
+/// stay dense.
+pub fn save_offer(
+    context: Context<MakeOffer>,
+    id: Option<u64>,
+    token_b_wanted_amount: u64,
+    rent_policy: RentPolicy,
+    maker_ata_rent_deposit: u64,
+    vesting: Option<VestingSchedule>,
+    arbiter: Option<Pubkey>,
+) -> Result<()> {
+    if let Some(schedule) = vesting {
+        require!(schedule.is_valid(), ErrorCode::InvalidVestingSchedule);
+        require!(arbiter.is_none(), ErrorCode::VestingWithArbiter);
+    }
+
+    let maker_state = &mut context.accounts.maker_state;
+    if maker_state.maker == Pubkey::default() {
+        maker_state.maker = context.accounts.maker.key();
+        maker_state.bump = context.bumps.maker_state;
+    }
+
+    let id = match id {
+        Some(id) => {
+            require_eq!(id, maker_state.next_offer_id, ErrorCode::UnexpectedOfferId);
+            id
+        }
+        None => maker_state.next_offer_id,
+    };
+    maker_state.next_offer_id = id.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+    maker_state.open_offer_count = maker_state
+        .open_offer_count
+        .checked_add(1)
+        .ok_or(ErrorCode::MathOverflow)?;
+
     context.accounts.offer.set_inner(Offer {
         id,
         maker: context.accounts.maker.key(),
         token_mint_a: context.accounts.token_mint_a.key(),
         token_mint_b: context.accounts.token_mint_b.key(),
         token_b_wanted_amount,
+        rent_policy,
+        maker_ata_rent_deposit,
+        vesting,
+        arbiter,
+        taker: None,
+        status: OfferStatus::Open,
         bump: context.bumps.offer,
     });
     Ok(())
@@ -4,3 +4,14 @@ pub mod take_offer;
 pub use take_offer::*;
 pub mod close_offer;
 pub use close_offer::*;
+pub mod claim_vested;
+pub use claim_vested::*;
+pub mod raise_dispute;
+pub use raise_dispute::*;
+pub mod resolve_dispute;
+pub use resolve_dispute::*;
+pub mod accept_offer;
+pub use accept_offer::*;
+pub mod complete_offer;
+pub use complete_offer::*;
+pub mod shared;
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, Offer, OfferStatus};
+
+#[derive(Accounts)]
+pub struct RaiseDispute<'info> {
+    pub arbiter: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"offer", offer.maker.as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump = offer.bump,
+        constraint = offer.arbiter == Some(arbiter.key()) @ ErrorCode::NotArbiter,
+        constraint = offer.status == OfferStatus::Accepted @ ErrorCode::OfferNotAccepted,
+    )]
+    pub offer: Account<'info, Offer>,
+}
+
+/// Freezes an accepted offer so only the arbiter can settle it.
+pub fn raise_dispute(context: Context<RaiseDispute>) -> Result<()> {
+    context.accounts.offer.status = OfferStatus::Disputed;
+    Ok(())
+}
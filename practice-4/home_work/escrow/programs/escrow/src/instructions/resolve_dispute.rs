@@ -0,0 +1,217 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::ErrorCode,
+    instructions::shared::{
+        create_maker_token_account_b, release_offer_vault, vault_rent_recipient,
+    },
+    MakerState, Offer, OfferStatus, RentParty,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeResolution {
+    /// Token A goes back to the maker, token B back to the taker.
+    ToMaker,
+    /// The trade goes through: token A to the taker, token B to the maker.
+    ToTaker,
+    /// `maker_amount` of token A goes back to the maker, the rest to the
+    /// taker. The maker is paid the same share of token B; the taker gets the
+    /// rest of their payment back.
+    Split { maker_amount: u64 },
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(mut)]
+    pub arbiter: Signer<'info>,
+
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub taker: SystemAccount<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mint::token_program = token_program)]
+    pub token_mint_b: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        close = maker,
+        seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump = offer.bump,
+        has_one = maker,
+        has_one = token_mint_a,
+        has_one = token_mint_b,
+        constraint = offer.taker == Some(taker.key()) @ ErrorCode::NotTaker,
+        constraint = offer.arbiter == Some(arbiter.key()) @ ErrorCode::NotArbiter,
+        constraint = offer.status == OfferStatus::Disputed @ ErrorCode::OfferNotDisputed,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        mut,
+        seeds = [b"maker_state", maker.key().as_ref()],
+        bump = maker_state.bump,
+        has_one = maker,
+    )]
+    pub maker_state: Box<Account<'info, MakerState>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = offer,
+        associated_token::token_program = token_program
+    )]
+    pub payment_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = arbiter,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_token_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = arbiter,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_token_account_a: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: created in the handler if missing, so the rent can be settled
+    /// according to `offer.rent_policy`.
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &maker.key(),
+            &token_mint_b.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub maker_token_account_b: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = arbiter,
+        associated_token::mint = token_mint_b,
+        associated_token::authority = taker,
+        associated_token::token_program = token_program
+    )]
+    pub taker_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Settles both vaults as decided by the arbiter. Rent follows
+/// `offer.rent_policy` as in `take_offer`, with the arbiter fronting the
+/// maker's token B ATA: if the taker is the one paying for it, the arbiter
+/// keeps the payment vault's rent, which the taker paid in `accept_offer` for
+/// an account of the same size. Otherwise that rent goes back to the taker.
+pub fn resolve_dispute(
+    context: Context<ResolveDispute>,
+    resolution: DisputeResolution,
+) -> Result<()> {
+    let vault_amount = context.accounts.vault.amount;
+    let payment_amount = context.accounts.payment_vault.amount;
+    let maker_amount_a = match resolution {
+        DisputeResolution::ToMaker => vault_amount,
+        DisputeResolution::ToTaker => 0,
+        DisputeResolution::Split { maker_amount } => {
+            require_gte!(vault_amount, maker_amount, ErrorCode::InvalidSplit);
+            maker_amount
+        }
+    };
+    let taker_amount_a = vault_amount - maker_amount_a;
+    // The maker is paid for the share of token A the taker ends up with.
+    let maker_amount_b = if vault_amount == 0 {
+        0
+    } else {
+        u64::try_from(payment_amount as u128 * taker_amount_a as u128 / vault_amount as u128)
+            .map_err(|_| ErrorCode::MathOverflow)?
+    };
+    let taker_amount_b = payment_amount - maker_amount_b;
+
+    let maker_state = &mut context.accounts.maker_state;
+    maker_state.open_offer_count = maker_state.open_offer_count.saturating_sub(1);
+
+    let accounts = &context.accounts;
+    let mut payment_vault_rent_recipient = accounts.taker.to_account_info();
+    if maker_amount_b > 0 {
+        let created = create_maker_token_account_b(
+            &accounts.offer,
+            associated_token::Create {
+                payer: accounts.arbiter.to_account_info(),
+                associated_token: accounts.maker_token_account_b.to_account_info(),
+                authority: accounts.maker.to_account_info(),
+                mint: accounts.token_mint_b.to_account_info(),
+                system_program: accounts.system_program.to_account_info(),
+                token_program: accounts.token_program.to_account_info(),
+            },
+            accounts.associated_token_program.to_account_info(),
+        )?;
+        if created && accounts.offer.rent_policy.maker_ata_payer == RentParty::Taker {
+            payment_vault_rent_recipient = accounts.arbiter.to_account_info();
+        }
+    }
+
+    release_offer_vault(
+        &accounts.offer,
+        &accounts.payment_vault,
+        &accounts.token_mint_b,
+        &[
+            (
+                accounts.maker_token_account_b.to_account_info(),
+                maker_amount_b,
+            ),
+            (
+                accounts.taker_token_account_b.to_account_info(),
+                taker_amount_b,
+            ),
+        ],
+        payment_vault_rent_recipient,
+        &accounts.token_program,
+    )?;
+    release_offer_vault(
+        &accounts.offer,
+        &accounts.vault,
+        &accounts.token_mint_a,
+        &[
+            (
+                accounts.maker_token_account_a.to_account_info(),
+                maker_amount_a,
+            ),
+            (
+                accounts.taker_token_account_a.to_account_info(),
+                taker_amount_a,
+            ),
+        ],
+        vault_rent_recipient(
+            &accounts.offer,
+            accounts.maker.to_account_info(),
+            accounts.taker.to_account_info(),
+        ),
+        &accounts.token_program,
+    )
+}
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token,
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
+};
+
+use crate::{Offer, RentParty};
+
+/// Creates the maker's token B ATA if it doesn't exist yet, with `accounts.payer`
+/// fronting the rent. If the maker agreed to pay for it, the payer is
+/// reimbursed from the deposit parked on the offer. Returns whether the ATA
+/// had to be created.
+pub fn create_maker_token_account_b<'info>(
+    offer: &Account<'info, Offer>,
+    accounts: associated_token::Create<'info>,
+    associated_token_program: AccountInfo<'info>,
+) -> Result<bool> {
+    let maker_token_account_b = accounts.associated_token.clone();
+    if !maker_token_account_b.data_is_empty() {
+        return Ok(false);
+    }
+
+    let payer = accounts.payer.clone();
+    associated_token::create(CpiContext::new(associated_token_program, accounts))?;
+
+    if offer.rent_policy.maker_ata_payer == RentParty::Maker {
+        let refund = maker_token_account_b
+            .lamports()
+            .min(offer.maker_ata_rent_deposit);
+        offer.sub_lamports(refund)?;
+        payer.add_lamports(refund)?;
+    }
+    Ok(true)
+}
+
+/// Picks whoever `offer.rent_policy` says receives the token A vault's rent.
+pub fn vault_rent_recipient<'info>(
+    offer: &Offer,
+    maker: AccountInfo<'info>,
+    taker: AccountInfo<'info>,
+) -> AccountInfo<'info> {
+    match offer.rent_policy.vault_rent_recipient {
+        RentParty::Maker => maker,
+        RentParty::Taker => taker,
+    }
+}
+
+/// Pays out an offer-owned vault to each `(destination, amount)` pair, then
+/// closes it and sends its rent to `rent_recipient`.
+pub fn release_offer_vault<'info>(
+    offer: &Account<'info, Offer>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    payouts: &[(AccountInfo<'info>, u64)],
+    rent_recipient: AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    let offer_id_bytes = offer.id.to_le_bytes();
+    let seeds = &[
+        b"offer",
+        offer.maker.as_ref(),
+        offer_id_bytes.as_ref(),
+        &[offer.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    for (destination, amount) in payouts {
+        if *amount == 0 {
+            continue;
+        }
+        let transfer_accounts = TransferChecked {
+            from: vault.to_account_info(),
+            mint: mint.to_account_info(),
+            to: destination.clone(),
+            authority: offer.to_account_info(),
+        };
+        let cpi_context =
+            CpiContext::new_with_signer(token_program.to_account_info(), transfer_accounts, signer);
+        transfer_checked(cpi_context, *amount, mint.decimals)?;
+    }
+
+    let close_accounts = CloseAccount {
+        account: vault.to_account_info(),
+        destination: rent_recipient,
+        authority: offer.to_account_info(),
+    };
+    let cpi_context =
+        CpiContext::new_with_signer(token_program.to_account_info(), close_accounts, signer);
+    close_account(cpi_context)
+}
@@ -1,7 +1,11 @@
-use crate::Offer;
+use crate::{
+    error::ErrorCode,
+    instructions::shared::{self, vault_rent_recipient},
+    MakerState, Offer, OfferStatus, Vesting, ANCHOR_DISCRIMINATOR,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{self, get_associated_token_address_with_program_id, AssociatedToken},
     token_interface::{
         close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
         TransferChecked,
@@ -33,14 +37,17 @@ pub struct TakeOffer<'info> {
         associated_token::token_program = token_program,
     )]
     pub taker_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: created in the handler if missing, so the rent can be settled
+    /// according to `offer.rent_policy`.
     #[account(
-        init_if_needed,
-        payer = taker,
-        associated_token::mint = token_mint_b,
-        associated_token::authority = maker,
-        associated_token::token_program = token_program,
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &maker.key(),
+            &token_mint_b.key(),
+            &token_program.key(),
+        ),
     )]
-    pub maker_token_account_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub maker_token_account_b: UncheckedAccount<'info>,
 
     #[account(
         mut,
@@ -48,10 +55,19 @@ pub struct TakeOffer<'info> {
         has_one = maker,
         has_one = token_mint_a,
         has_one = token_mint_b,
+        constraint = offer.status == OfferStatus::Open @ ErrorCode::OfferNotOpen,
+        constraint = offer.arbiter.is_none() @ ErrorCode::OfferHasArbiter,
         // seeds = [b"offer", maker.key().as_ref(), offer.id.to_le_bytes().as_ref()],
         // bump = offer.bump
     )]
     offer: Account<'info, Offer>,
+    #[account(
+        mut,
+        seeds = [b"maker_state", maker.key().as_ref()],
+        bump = maker_state.bump,
+        has_one = maker,
+    )]
+    pub maker_state: Box<Account<'info, MakerState>>,
     #[account(
         mut,
         associated_token::mint = token_mint_a,
@@ -60,11 +76,67 @@ pub struct TakeOffer<'info> {
     )]
     pub vault: InterfaceAccount<'info, TokenAccount>,
 
+    /// Only required when the offer has a vesting schedule.
+    #[account(
+        init,
+        payer = taker,
+        space = ANCHOR_DISCRIMINATOR + Vesting::INIT_SPACE,
+        seeds = [b"vesting", offer.key().as_ref()],
+        bump
+    )]
+    pub vesting: Option<Box<Account<'info, Vesting>>>,
+    #[account(
+        init,
+        payer = taker,
+        associated_token::mint = token_mint_a,
+        associated_token::authority = vesting,
+        associated_token::token_program = token_program,
+    )]
+    pub vesting_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
+/// Guards the taker against the offer or vault changing between the moment
+/// they looked at it and the moment their transaction lands.
+pub fn check_offer_terms(
+    ctx: &Context<TakeOffer>,
+    expected_token_b_amount: u64,
+    min_token_a_received: u64,
+) -> Result<()> {
+    require_eq!(
+        ctx.accounts.offer.token_b_wanted_amount,
+        expected_token_b_amount,
+        ErrorCode::OfferTermsChanged
+    );
+    require_gte!(
+        ctx.accounts.vault.amount,
+        min_token_a_received,
+        ErrorCode::SlippageExceeded
+    );
+    Ok(())
+}
+
+/// Creates the maker's token B ATA if it doesn't exist yet, settling its rent
+/// between the taker and the maker's deposit.
+pub fn create_maker_token_account_b(ctx: &Context<TakeOffer>) -> Result<()> {
+    shared::create_maker_token_account_b(
+        &ctx.accounts.offer,
+        associated_token::Create {
+            payer: ctx.accounts.taker.to_account_info(),
+            associated_token: ctx.accounts.maker_token_account_b.to_account_info(),
+            authority: ctx.accounts.maker.to_account_info(),
+            mint: ctx.accounts.token_mint_b.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+        ctx.accounts.associated_token_program.to_account_info(),
+    )?;
+    Ok(())
+}
+
 pub fn send_wanted_tokens_to_maker(ctx: &Context<TakeOffer>) -> Result<()> {
     let transfer_accounts = TransferChecked {
         from: ctx.accounts.taker_token_account_b.to_account_info(),
@@ -84,7 +156,47 @@ pub fn send_wanted_tokens_to_maker(ctx: &Context<TakeOffer>) -> Result<()> {
     )
 }
 
+/// Sends token A to the taker, or into their vesting account when the offer
+/// has a vesting schedule, then closes the offer vault.
 pub fn withdraw_and_close_vault(ctx: Context<TakeOffer>) -> Result<()> {
+    let maker_state = &mut ctx.accounts.maker_state;
+    maker_state.open_offer_count = maker_state.open_offer_count.saturating_sub(1);
+
+    let token_a_destination = match ctx.accounts.offer.vesting {
+        Some(schedule) => {
+            let (Some(vesting), Some(vesting_vault)) =
+                (&mut ctx.accounts.vesting, &ctx.accounts.vesting_vault)
+            else {
+                return err!(ErrorCode::VestingAccountsMissing);
+            };
+            let start_ts = Clock::get()?.unix_timestamp;
+            vesting.set_inner(Vesting {
+                offer: ctx.accounts.offer.key(),
+                taker: ctx.accounts.taker.key(),
+                token_mint_a: ctx.accounts.token_mint_a.key(),
+                total_amount: ctx.accounts.vault.amount,
+                claimed_amount: 0,
+                start_ts,
+                cliff_ts: start_ts
+                    .checked_add(schedule.cliff_seconds)
+                    .ok_or(ErrorCode::MathOverflow)?,
+                end_ts: start_ts
+                    .checked_add(schedule.duration_seconds)
+                    .ok_or(ErrorCode::MathOverflow)?,
+                bump: ctx.bumps.vesting.unwrap(),
+            });
+            vesting_vault.to_account_info()
+        }
+        None => {
+            // They would be created at the taker's expense and never used.
+            require!(
+                ctx.accounts.vesting.is_none() && ctx.accounts.vesting_vault.is_none(),
+                ErrorCode::UnexpectedVestingAccounts
+            );
+            ctx.accounts.taker_token_account_a.to_account_info()
+        }
+    };
+
     let signer_seeds: [&[&[u8]]; 1] = [&[
         b"offer",
         ctx.accounts.maker.to_account_info().key.as_ref(),
@@ -95,7 +207,7 @@ pub fn withdraw_and_close_vault(ctx: Context<TakeOffer>) -> Result<()> {
     let accounts = TransferChecked {
         from: ctx.accounts.vault.to_account_info(),
         mint: ctx.accounts.token_mint_a.to_account_info(),
-        to: ctx.accounts.taker_token_account_a.to_account_info(),
+        to: token_a_destination,
         authority: ctx.accounts.offer.to_account_info(),
     };
 
@@ -111,7 +223,11 @@ pub fn withdraw_and_close_vault(ctx: Context<TakeOffer>) -> Result<()> {
     )?;
     let accounts = CloseAccount {
         account: ctx.accounts.vault.to_account_info(),
-        destination: ctx.accounts.taker.to_account_info(),
+        destination: vault_rent_recipient(
+            &ctx.accounts.offer,
+            ctx.accounts.maker.to_account_info(),
+            ctx.accounts.taker.to_account_info(),
+        ),
         authority: ctx.accounts.offer.to_account_info(),
     };
 
@@ -4,7 +4,10 @@ pub mod instructions;
 pub mod state;
 
 use anchor_lang::prelude::*;
-use instructions::{close_offer::*, make_offer::*, take_offer::*};
+use instructions::{
+    accept_offer::*, claim_vested::*, close_offer::*, complete_offer::*, make_offer::*,
+    raise_dispute::*, resolve_dispute::*, take_offer::*,
+};
 
 pub use constants::*;
 pub use state::*;
@@ -19,20 +22,66 @@ pub mod escrow {
 
     pub fn make_offer(
         context: Context<MakeOffer>,
-        id: u64,
+        id: Option<u64>,
         token_a_offered_amount: u64,
         token_b_wanted_amount: u64,
+        rent_policy: RentPolicy,
+        vesting: Option<VestingSchedule>,
+        arbiter: Option<Pubkey>,
     ) -> Result<()> {
         instructions::make_offer::send_offered_tokens_to_vault(&context, token_a_offered_amount)?;
-        instructions::make_offer::save_offer(context, id, token_b_wanted_amount)
+        let maker_ata_rent_deposit =
+            instructions::make_offer::deposit_maker_ata_rent(&context, &rent_policy)?;
+        instructions::make_offer::save_offer(
+            context,
+            id,
+            token_b_wanted_amount,
+            rent_policy,
+            maker_ata_rent_deposit,
+            vesting,
+            arbiter,
+        )
     }
 
-    pub fn take_offer(context: Context<TakeOffer>) -> Result<()> {
+    pub fn take_offer(
+        context: Context<TakeOffer>,
+        expected_token_b_amount: u64,
+        min_token_a_received: u64,
+    ) -> Result<()> {
+        instructions::take_offer::check_offer_terms(
+            &context,
+            expected_token_b_amount,
+            min_token_a_received,
+        )?;
+        instructions::take_offer::create_maker_token_account_b(&context)?;
         instructions::take_offer::send_wanted_tokens_to_maker(&context)?;
         instructions::take_offer::withdraw_and_close_vault(context)
     }
 
+    pub fn claim_vested(context: Context<ClaimVested>) -> Result<()> {
+        instructions::claim_vested::claim_vested(context)
+    }
+
     pub fn close_offer(context: Context<CloseOffer>) -> Result<()> {
         instructions::close_offer::close_offer(context)
     }
+
+    pub fn accept_offer(context: Context<AcceptOffer>, expected_token_b_amount: u64) -> Result<()> {
+        instructions::accept_offer::accept_offer(context, expected_token_b_amount)
+    }
+
+    pub fn complete_offer(context: Context<CompleteOffer>) -> Result<()> {
+        instructions::complete_offer::complete_offer(context)
+    }
+
+    pub fn raise_dispute(context: Context<RaiseDispute>) -> Result<()> {
+        instructions::raise_dispute::raise_dispute(context)
+    }
+
+    pub fn resolve_dispute(
+        context: Context<ResolveDispute>,
+        resolution: DisputeResolution,
+    ) -> Result<()> {
+        instructions::resolve_dispute::resolve_dispute(context, resolution)
+    }
 }
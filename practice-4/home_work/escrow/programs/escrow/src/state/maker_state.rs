@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+/// Per-maker bookkeeping, so clients don't have to pick offer IDs themselves
+/// and can enumerate a maker's offers by walking `0..next_offer_id`: offer IDs
+/// are handed out in order, and an explicit ID is only accepted when it equals
+/// `next_offer_id`.
+#[account]
+#[derive(InitSpace)]
+pub struct MakerState {
+    pub maker: Pubkey,
+    pub next_offer_id: u64,
+    pub open_offer_count: u64,
+    pub bump: u8,
+}
@@ -1,2 +1,6 @@
 pub mod offer;
 pub use offer::*;
+pub mod maker_state;
+pub use maker_state::*;
+pub mod vesting;
+pub use vesting::*;
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::VestingSchedule;
+
 #[account]
 #[derive(InitSpace)]
 pub struct Offer {
@@ -8,5 +10,41 @@ pub struct Offer {
     pub token_mint_a: Pubkey,
     pub token_mint_b: Pubkey,
     pub token_b_wanted_amount: u64,
+    pub rent_policy: RentPolicy,
+    /// Lamports the maker parked on the offer to cover their token B ATA.
+    pub maker_ata_rent_deposit: u64,
+    /// When set, the taker receives token A through a `Vesting` account.
+    pub vesting: Option<VestingSchedule>,
+    /// Third party allowed to freeze the offer and decide where the vaults go.
+    pub arbiter: Option<Pubkey>,
+    /// Set by `accept_offer`: the taker whose token B sits in the payment vault.
+    pub taker: Option<Pubkey>,
+    pub status: OfferStatus,
     pub bump: u8,
 }
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum OfferStatus {
+    Open,
+    /// A taker locked their token B in the payment vault, waiting for them to
+    /// `complete_offer` or for the arbiter to step in.
+    Accepted,
+    /// Frozen by the arbiter, only `resolve_dispute` can settle it.
+    Disputed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum RentParty {
+    Maker,
+    Taker,
+}
+
+/// Who carries the rent of the accounts created and closed when the offer is
+/// settled by `take_offer`, `complete_offer` or `resolve_dispute`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct RentPolicy {
+    /// Who ends up paying for the maker's token B ATA if the taker has to create it.
+    pub maker_ata_payer: RentParty,
+    /// Who receives the lamports of the closed vault.
+    pub vault_rent_recipient: RentParty,
+}
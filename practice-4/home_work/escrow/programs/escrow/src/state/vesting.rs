@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+
+/// Release schedule chosen by the maker, relative to the moment the offer is taken.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct VestingSchedule {
+    pub cliff_seconds: i64,
+    pub duration_seconds: i64,
+}
+
+impl VestingSchedule {
+    pub fn is_valid(&self) -> bool {
+        self.duration_seconds > 0
+            && self.cliff_seconds >= 0
+            && self.cliff_seconds <= self.duration_seconds
+    }
+}
+
+/// Token A bought by a taker, released linearly between `start_ts` and `end_ts`
+/// with nothing claimable before `cliff_ts`.
+#[account]
+#[derive(InitSpace)]
+pub struct Vesting {
+    pub offer: Pubkey,
+    pub taker: Pubkey,
+    pub token_mint_a: Pubkey,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub start_ts: i64,
+    pub cliff_ts: i64,
+    pub end_ts: i64,
+    pub bump: u8,
+}
+
+impl Vesting {
+    pub fn unlocked_amount(&self, now: i64) -> u64 {
+        if now < self.cliff_ts {
+            return 0;
+        }
+        if now >= self.end_ts {
+            return self.total_amount;
+        }
+        let elapsed = (now - self.start_ts) as u128;
+        let duration = (self.end_ts - self.start_ts) as u128;
+        (self.total_amount as u128 * elapsed / duration) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vesting(total_amount: u64) -> Vesting {
+        Vesting {
+            offer: Pubkey::default(),
+            taker: Pubkey::default(),
+            token_mint_a: Pubkey::default(),
+            total_amount,
+            claimed_amount: 0,
+            start_ts: 1_000,
+            cliff_ts: 1_250,
+            end_ts: 2_000,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn nothing_unlocks_before_the_cliff() {
+        let vesting = vesting(1_000_000);
+        assert_eq!(vesting.unlocked_amount(0), 0);
+        assert_eq!(vesting.unlocked_amount(1_000), 0);
+        assert_eq!(vesting.unlocked_amount(1_249), 0);
+    }
+
+    #[test]
+    fn cliff_releases_everything_accrued_since_start() {
+        let vesting = vesting(1_000_000);
+        assert_eq!(vesting.unlocked_amount(1_250), 250_000);
+    }
+
+    #[test]
+    fn unlocks_linearly_until_the_end() {
+        let vesting = vesting(1_000_000);
+        assert_eq!(vesting.unlocked_amount(1_500), 500_000);
+        assert_eq!(vesting.unlocked_amount(1_999), 999_000);
+        assert_eq!(vesting.unlocked_amount(2_000), 1_000_000);
+        assert_eq!(vesting.unlocked_amount(i64::MAX), 1_000_000);
+    }
+
+    #[test]
+    fn rounds_down_and_handles_large_amounts() {
+        let vesting = vesting(u64::MAX);
+        assert_eq!(vesting.unlocked_amount(1_500), u64::MAX / 2);
+        assert_eq!(vesting.unlocked_amount(2_000), u64::MAX);
+
+        let vesting = Vesting { total_amount: 3, ..vesting };
+        assert_eq!(vesting.unlocked_amount(1_500), 1);
+    }
+}
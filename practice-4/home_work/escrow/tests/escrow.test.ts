@@ -97,6 +97,17 @@ const getTokenBalanceOn = (
         return new BN(tokenBalance.value.amount);
     };
 
+type RentPolicy = {
+    makerAtaPayer: { maker: {} } | { taker: {} };
+    vaultRentRecipient: { maker: {} } | { taker: {} };
+};
+
+// Matches the behaviour before rent policies existed.
+const TAKER_PAYS_RENT: RentPolicy = {
+    makerAtaPayer: { taker: {} },
+    vaultRentRecipient: { taker: {} },
+};
+
 // Jest debug console it too verbose.
 // const jestConsole = console;
 
@@ -114,7 +125,7 @@ describe("escrow", () => {
 
     const program = anchor.workspace.Escrow as Program<Escrow>;
 
-    const [alice, bob, usdcMint, wifMint] = makeKeypairs(4);
+    const [alice, bob, arbiter, usdcMint, wifMint] = makeKeypairs(5);
 
     const [aliceUsdcAccount, aliceWifAccount, bobUsdcAccount, bobWifAccount] = [
         alice,
@@ -130,8 +141,8 @@ describe("escrow", () => {
         )
     );
 
-    // Pick a random ID for the new offer.
-    const offerId = getRandomBigNumber();
+    // Alice's first offer, so it must take ID 0 from her maker state.
+    const offerId = new BN(0);
 
     // Creates Alice and Bob accounts, 2 token mints, and associated token
     // accounts for both tokens for both users.
@@ -141,6 +152,7 @@ describe("escrow", () => {
         const giveAliceAndBobSolIxs: Array<TransactionInstruction> = [
             alice,
             bob,
+            arbiter,
         ].map((owner) =>
             SystemProgram.transfer({
                 fromPubkey: provider.publicKey,
@@ -193,19 +205,37 @@ describe("escrow", () => {
     //   global.console = jestConsole;
     // });
 
+    const makerStateAddress = (maker: Keypair): PublicKey =>
+        PublicKey.findProgramAddressSync(
+            [Buffer.from("maker_state"), maker.publicKey.toBuffer()],
+            program.programId
+        )[0];
+
+    // The ID `make_offer` allocates when none is given.
+    const nextOfferId = async (maker: Keypair): Promise<BN> => {
+        const makerState = await program.account.makerState.fetchNullable(
+            makerStateAddress(maker)
+        );
+        return makerState?.nextOfferId ?? new BN(0);
+    };
+
     const makeOfferTx = async (
         maker: Keypair,
-        offerId: BN,
+        offerId: BN | null,
         offeredTokenMint: PublicKey,
         offeredAmount: BN,
         wantedTokenMint: PublicKey,
-        wantedAmount: BN
+        wantedAmount: BN,
+        rentPolicy: RentPolicy = TAKER_PAYS_RENT,
+        vesting: { cliffSeconds: BN; durationSeconds: BN } | null = null,
+        arbiter: PublicKey | null = null
     ): Promise<{
         offerAddress: PublicKey;
         vaultAddress: PublicKey;
     }> => {
+        const id = offerId ?? (await nextOfferId(maker));
         const transactionSignature = await program.methods
-            .makeOffer(offerId, offeredAmount, wantedAmount)
+            .makeOffer(offerId, offeredAmount, wantedAmount, rentPolicy, vesting, arbiter)
             .accounts({
                 maker: maker.publicKey,
                 tokenMintA: offeredTokenMint,
@@ -222,7 +252,7 @@ describe("escrow", () => {
             [
                 Buffer.from("offer"),
                 maker.publicKey.toBuffer(),
-                offerId.toArrayLike(Buffer, "le", 8),
+                id.toArrayLike(Buffer, "le", 8),
             ],
             program.programId
         );
@@ -240,19 +270,32 @@ describe("escrow", () => {
     const takeOfferTx = async (
         offerAddress: PublicKey,
         taker: Keypair,
-    ): Promise<void> => {
+        expectedTokenBAmount: BN,
+        minTokenAReceived: BN,
+        vestingAccounts: { vesting: PublicKey; vestingVault: PublicKey } | null = null,
+    ): Promise<string> => {
+        const { maker, tokenMintB } = await program.account.offer.fetch(offerAddress);
 
         const transactionSignature = await program.methods
-            .takeOffer()
+            .takeOffer(expectedTokenBAmount, minTokenAReceived)
             .accounts({
                 taker: taker.publicKey,
                 offer: offerAddress,
+                makerTokenAccountB: getAssociatedTokenAddressSync(
+                    tokenMintB,
+                    maker,
+                    false,
+                    TOKEN_PROGRAM
+                ),
                 tokenProgram: TOKEN_PROGRAM,
-            })
+                vesting: vestingAccounts?.vesting ?? null,
+                vestingVault: vestingAccounts?.vestingVault ?? null,
+            } as any)
             .signers([taker])
             .rpc();
 
         await confirmTransaction(connection, transactionSignature);
+        return transactionSignature;
     };
 
     const closeOfferTx = async (
@@ -339,7 +382,7 @@ describe("escrow", () => {
         expect(await getTokenBalance(bobUsdcAccount)).toEqual(new BN(20_000_000));
         expect(await getTokenBalance(bobWifAccount)).toEqual(new BN(300_000_000));
 
-        await takeOfferTx(offerAddress, bob);
+        await takeOfferTx(offerAddress, bob, new BN(100_000_000), new BN(10_000_000));
 
         expect(await getTokenBalance(aliceUsdcAccount)).toEqual(new BN(90_000_000));
         expect(await getTokenBalance(aliceWifAccount)).toEqual(new BN(105_000_000));
@@ -359,7 +402,7 @@ describe("escrow", () => {
         // Make the offer
         const { offerAddress, vaultAddress } = await makeOfferTx(
             alice,
-            offerId,
+            null,
             usdcMint.publicKey,
             offeredAmount,
             wifMint.publicKey,
@@ -398,7 +441,7 @@ describe("escrow", () => {
         // Make the offer
         const { offerAddress } = await makeOfferTx(
             alice,
-            offerId,
+            null,
             usdcMint.publicKey,
             offeredAmount,
             wifMint.publicKey,
@@ -415,4 +458,752 @@ describe("escrow", () => {
         ).rejects.toThrow();
     });
 
-});
\ No newline at end of file
+    test("Bob cannot take an offer whose wanted amount differs from expected", async () => {
+        const { offerAddress } = await makeOfferTx(
+            alice,
+            null,
+            usdcMint.publicKey,
+            new BN(1_000_000),
+            wifMint.publicKey,
+            new BN(2_000_000)
+        );
+
+        await expect(
+            takeOfferTx(offerAddress, bob, new BN(1_000_000), new BN(1_000_000))
+        ).rejects.toThrow(/OfferTermsChanged/);
+    });
+
+    test("Bob cannot take an offer when the vault holds less than his minimum", async () => {
+        const { offerAddress } = await makeOfferTx(
+            alice,
+            null,
+            usdcMint.publicKey,
+            new BN(1_000_000),
+            wifMint.publicKey,
+            new BN(2_000_000)
+        );
+
+        await expect(
+            takeOfferTx(offerAddress, bob, new BN(2_000_000), new BN(1_000_001))
+        ).rejects.toThrow(/SlippageExceeded/);
+    });
+
+    test("Offer ID allocated from Alice's maker state when none is given", async () => {
+        const aliceMakerState = makerStateAddress(alice);
+        const before = await program.account.makerState.fetch(aliceMakerState);
+
+        const transactionSignature = await program.methods
+            .makeOffer(null, new BN(1_000_000), new BN(2_000_000), TAKER_PAYS_RENT, null, null)
+            .accounts({
+                maker: alice.publicKey,
+                tokenMintA: usdcMint.publicKey,
+                tokenMintB: wifMint.publicKey,
+                tokenProgram: TOKEN_PROGRAM,
+            })
+            .signers([alice])
+            .rpc();
+        await confirmTransaction(connection, transactionSignature);
+
+        const [offerAddress] = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("offer"),
+                alice.publicKey.toBuffer(),
+                before.nextOfferId.toArrayLike(Buffer, "le", 8),
+            ],
+            program.programId
+        );
+        const offerAccount = await program.account.offer.fetch(offerAddress);
+        expect(offerAccount.id).toEqual(before.nextOfferId);
+
+        const after = await program.account.makerState.fetch(aliceMakerState);
+        expect(after.maker).toEqual(alice.publicKey);
+        expect(after.nextOfferId).toEqual(before.nextOfferId.addn(1));
+        expect(after.openOfferCount).toEqual(before.openOfferCount.addn(1));
+
+        await closeOfferTx(alice, offerAddress, usdcMint.publicKey);
+
+        const closed = await program.account.makerState.fetch(aliceMakerState);
+        expect(closed.nextOfferId).toEqual(after.nextOfferId);
+        expect(closed.openOfferCount).toEqual(before.openOfferCount);
+    });
+
+    test("Explicit offer IDs must match the next allocated ID", async () => {
+        const start = await nextOfferId(alice);
+
+        await expect(
+            makeOfferTx(
+                alice,
+                start.addn(5),
+                usdcMint.publicKey,
+                new BN(1_000_000),
+                wifMint.publicKey,
+                new BN(2_000_000)
+            )
+        ).rejects.toThrow(/UnexpectedOfferId/);
+        expect(await nextOfferId(alice)).toEqual(start);
+
+        const { offerAddress } = await makeOfferTx(
+            alice,
+            start,
+            usdcMint.publicKey,
+            new BN(1_000_000),
+            wifMint.publicKey,
+            new BN(2_000_000)
+        );
+        const offerAccount = await program.account.offer.fetch(offerAddress);
+        expect(offerAccount.id).toEqual(start);
+        expect(await nextOfferId(alice)).toEqual(start.addn(1));
+
+        await expect(
+            makeOfferTx(
+                alice,
+                start,
+                usdcMint.publicKey,
+                new BN(1_000_000),
+                wifMint.publicKey,
+                new BN(2_000_000)
+            )
+        ).rejects.toThrow(/UnexpectedOfferId/);
+    });
+
+    // A fresh maker holding only USDC, so whoever settles their offer has to
+    // create their WIF account.
+    const fundUsdcOnlyMaker = async (): Promise<{ maker: Keypair; wifAccount: PublicKey }> => {
+        const maker = Keypair.generate();
+        const usdcAccount = getAssociatedTokenAddressSync(
+            usdcMint.publicKey,
+            maker.publicKey,
+            false,
+            TOKEN_PROGRAM
+        );
+
+        let tx = new Transaction();
+        tx.instructions = [
+            SystemProgram.transfer({
+                fromPubkey: provider.publicKey,
+                toPubkey: maker.publicKey,
+                lamports: LAMPORTS_PER_SOL,
+            }),
+            createAssociatedTokenAccountIdempotentInstruction(
+                provider.publicKey,
+                usdcAccount,
+                maker.publicKey,
+                usdcMint.publicKey,
+                TOKEN_PROGRAM
+            ),
+            createMintToInstruction(
+                usdcMint.publicKey,
+                usdcAccount,
+                alice.publicKey,
+                1_000_000,
+                [],
+                TOKEN_PROGRAM
+            ),
+        ];
+        await provider.sendAndConfirm(tx, [alice]);
+
+        return {
+            maker,
+            wifAccount: getAssociatedTokenAddressSync(
+                wifMint.publicKey,
+                maker.publicKey,
+                false,
+                TOKEN_PROGRAM
+            ),
+        };
+    };
+
+    const transactionFee = async (signature: string): Promise<number> =>
+        (
+            await connection.getTransaction(signature, {
+                commitment: "confirmed",
+                maxSupportedTransactionVersion: 0,
+            })
+        ).meta.fee;
+
+    test("Maker reimburses the taker for the ATA rent and receives the vault rent", async () => {
+        const { maker: carol, wifAccount: carolWifAccount } = await fundUsdcOnlyMaker();
+
+        const { offerAddress, vaultAddress } = await makeOfferTx(
+            carol,
+            null,
+            usdcMint.publicKey,
+            new BN(1_000_000),
+            wifMint.publicKey,
+            new BN(2_000_000),
+            { makerAtaPayer: { maker: {} }, vaultRentRecipient: { maker: {} } }
+        );
+
+        const offerLamports = await connection.getBalance(offerAddress);
+        const vaultLamports = await connection.getBalance(vaultAddress);
+        const carolLamportsBefore = await connection.getBalance(carol.publicKey);
+        const bobLamportsBefore = await connection.getBalance(bob.publicKey);
+
+        const signature = await takeOfferTx(
+            offerAddress,
+            bob,
+            new BN(2_000_000),
+            new BN(1_000_000)
+        );
+        const fee = await transactionFee(signature);
+
+        const carolWifLamports = await connection.getBalance(carolWifAccount);
+        expect(carolWifLamports).toBeGreaterThan(0);
+
+        // Bob only paid the transaction fee.
+        expect(await connection.getBalance(bob.publicKey)).toEqual(
+            bobLamportsBefore - fee
+        );
+        // Carol got the offer and vault rent back, minus her new ATA.
+        expect(await connection.getBalance(carol.publicKey)).toEqual(
+            carolLamportsBefore + offerLamports + vaultLamports - carolWifLamports
+        );
+    });
+
+    const vestingAccountsFor = (offerAddress: PublicKey) => {
+        const [vesting] = PublicKey.findProgramAddressSync(
+            [Buffer.from("vesting"), offerAddress.toBuffer()],
+            program.programId
+        );
+        const vestingVault = getAssociatedTokenAddressSync(
+            usdcMint.publicKey,
+            vesting,
+            true,
+            TOKEN_PROGRAM
+        );
+        return { vesting, vestingVault };
+    };
+
+    const claimVestedTx = async (taker: Keypair, vesting: PublicKey): Promise<void> => {
+        const transactionSignature = await program.methods
+            .claimVested()
+            .accounts({
+                taker: taker.publicKey,
+                tokenMintA: usdcMint.publicKey,
+                vesting,
+                tokenProgram: TOKEN_PROGRAM,
+            } as any)
+            .signers([taker])
+            .rpc();
+
+        await confirmTransaction(connection, transactionSignature);
+    };
+
+    test("Vested offer locks token A until Bob claims it", async () => {
+        const getTokenBalance = getTokenBalanceOn(connection);
+        const offeredUsdc = new BN(1_000_000);
+
+        const { offerAddress } = await makeOfferTx(
+            alice,
+            null,
+            usdcMint.publicKey,
+            offeredUsdc,
+            wifMint.publicKey,
+            new BN(2_000_000),
+            TAKER_PAYS_RENT,
+            { cliffSeconds: new BN(0), durationSeconds: new BN(1) }
+        );
+        const vestingAccounts = vestingAccountsFor(offerAddress);
+
+        const bobUsdcBefore = await getTokenBalance(bobUsdcAccount);
+        const aliceWifBefore = await getTokenBalance(aliceWifAccount);
+
+        await takeOfferTx(
+            offerAddress,
+            bob,
+            new BN(2_000_000),
+            offeredUsdc,
+            vestingAccounts
+        );
+
+        // Alice is paid right away, Bob's USDC sits in the vesting vault.
+        expect(await getTokenBalance(aliceWifAccount)).toEqual(
+            aliceWifBefore.add(new BN(2_000_000))
+        );
+        expect(await getTokenBalance(bobUsdcAccount)).toEqual(bobUsdcBefore);
+        expect(await getTokenBalance(vestingAccounts.vestingVault)).toEqual(offeredUsdc);
+
+        const vestingAccount = await program.account.vesting.fetch(vestingAccounts.vesting);
+        expect(vestingAccount.taker).toEqual(bob.publicKey);
+        expect(vestingAccount.totalAmount).toEqual(offeredUsdc);
+
+        await new Promise((resolve) => setTimeout(resolve, 2_000));
+        await claimVestedTx(bob, vestingAccounts.vesting);
+
+        expect(await getTokenBalance(bobUsdcAccount)).toEqual(bobUsdcBefore.add(offeredUsdc));
+        expect(await connection.getAccountInfo(vestingAccounts.vesting)).toBeNull();
+        expect(await connection.getAccountInfo(vestingAccounts.vestingVault)).toBeNull();
+    });
+
+    test("Bob cannot claim before the cliff", async () => {
+        const { offerAddress } = await makeOfferTx(
+            alice,
+            null,
+            usdcMint.publicKey,
+            new BN(1_000_000),
+            wifMint.publicKey,
+            new BN(2_000_000),
+            TAKER_PAYS_RENT,
+            { cliffSeconds: new BN(3_600), durationSeconds: new BN(7_200) }
+        );
+        const vestingAccounts = vestingAccountsFor(offerAddress);
+
+        await takeOfferTx(
+            offerAddress,
+            bob,
+            new BN(2_000_000),
+            new BN(1_000_000),
+            vestingAccounts
+        );
+
+        await expect(claimVestedTx(bob, vestingAccounts.vesting)).rejects.toThrow(
+            /NothingToClaim/
+        );
+    });
+
+    test("Bob claims a partial unlock, then the rest", async () => {
+        const getTokenBalance = getTokenBalanceOn(connection);
+        const offeredUsdc = new BN(1_000_000);
+
+        const { offerAddress } = await makeOfferTx(
+            alice,
+            null,
+            usdcMint.publicKey,
+            offeredUsdc,
+            wifMint.publicKey,
+            new BN(2_000_000),
+            TAKER_PAYS_RENT,
+            { cliffSeconds: new BN(0), durationSeconds: new BN(8) }
+        );
+        const vestingAccounts = vestingAccountsFor(offerAddress);
+
+        const bobUsdcBefore = await getTokenBalance(bobUsdcAccount);
+        await takeOfferTx(
+            offerAddress,
+            bob,
+            new BN(2_000_000),
+            offeredUsdc,
+            vestingAccounts
+        );
+
+        // Part-way through the schedule only a share has unlocked.
+        await new Promise((resolve) => setTimeout(resolve, 2_000));
+        await claimVestedTx(bob, vestingAccounts.vesting);
+
+        const firstClaim = (await getTokenBalance(bobUsdcAccount)).sub(bobUsdcBefore);
+        expect(firstClaim.gtn(0)).toBe(true);
+        expect(firstClaim.lt(offeredUsdc)).toBe(true);
+
+        const vestingAccount = await program.account.vesting.fetch(vestingAccounts.vesting);
+        expect(vestingAccount.claimedAmount).toEqual(firstClaim);
+        expect(await getTokenBalance(vestingAccounts.vestingVault)).toEqual(
+            offeredUsdc.sub(firstClaim)
+        );
+
+        await new Promise((resolve) => setTimeout(resolve, 8_000));
+        await claimVestedTx(bob, vestingAccounts.vesting);
+
+        const secondClaim = (await getTokenBalance(bobUsdcAccount))
+            .sub(bobUsdcBefore)
+            .sub(firstClaim);
+        expect(firstClaim.add(secondClaim)).toEqual(offeredUsdc);
+        expect(await connection.getAccountInfo(vestingAccounts.vesting)).toBeNull();
+        expect(await connection.getAccountInfo(vestingAccounts.vestingVault)).toBeNull();
+    });
+
+    test("Vested offer cannot be taken without the vesting accounts", async () => {
+        const { offerAddress } = await makeOfferTx(
+            alice,
+            null,
+            usdcMint.publicKey,
+            new BN(1_000_000),
+            wifMint.publicKey,
+            new BN(2_000_000),
+            TAKER_PAYS_RENT,
+            { cliffSeconds: new BN(0), durationSeconds: new BN(60) }
+        );
+
+        await expect(
+            takeOfferTx(offerAddress, bob, new BN(2_000_000), new BN(1_000_000))
+        ).rejects.toThrow(/VestingAccountsMissing/);
+    });
+
+    test("Vesting accounts are rejected for an offer without vesting", async () => {
+        const { offerAddress } = await makeOfferTx(
+            alice,
+            null,
+            usdcMint.publicKey,
+            new BN(1_000_000),
+            wifMint.publicKey,
+            new BN(2_000_000)
+        );
+
+        await expect(
+            takeOfferTx(
+                offerAddress,
+                bob,
+                new BN(2_000_000),
+                new BN(1_000_000),
+                vestingAccountsFor(offerAddress)
+            )
+        ).rejects.toThrow(/UnexpectedVestingAccounts/);
+    });
+
+    test("Vesting schedule overflowing the clock is rejected on take", async () => {
+        const maxI64 = new BN("9223372036854775807");
+        const { offerAddress } = await makeOfferTx(
+            alice,
+            null,
+            usdcMint.publicKey,
+            new BN(1_000_000),
+            wifMint.publicKey,
+            new BN(2_000_000),
+            TAKER_PAYS_RENT,
+            { cliffSeconds: new BN(0), durationSeconds: maxI64 }
+        );
+
+        await expect(
+            takeOfferTx(
+                offerAddress,
+                bob,
+                new BN(2_000_000),
+                new BN(1_000_000),
+                vestingAccountsFor(offerAddress)
+            )
+        ).rejects.toThrow(/MathOverflow/);
+    });
+
+    const acceptOfferTx = async (
+        taker: Keypair,
+        offerAddress: PublicKey,
+        expectedTokenBAmount: BN
+    ): Promise<void> => {
+        const transactionSignature = await program.methods
+            .acceptOffer(expectedTokenBAmount)
+            .accounts({
+                taker: taker.publicKey,
+                tokenMintB: wifMint.publicKey,
+                offer: offerAddress,
+                tokenProgram: TOKEN_PROGRAM,
+            } as any)
+            .signers([taker])
+            .rpc();
+
+        await confirmTransaction(connection, transactionSignature);
+    };
+
+    const completeOfferTx = async (taker: Keypair, offerAddress: PublicKey): Promise<string> => {
+        const { maker, tokenMintA, tokenMintB } = await program.account.offer.fetch(offerAddress);
+
+        const transactionSignature = await program.methods
+            .completeOffer()
+            .accounts({
+                taker: taker.publicKey,
+                maker,
+                tokenMintA,
+                tokenMintB,
+                offer: offerAddress,
+                makerTokenAccountB: getAssociatedTokenAddressSync(
+                    tokenMintB,
+                    maker,
+                    false,
+                    TOKEN_PROGRAM
+                ),
+                tokenProgram: TOKEN_PROGRAM,
+            } as any)
+            .signers([taker])
+            .rpc();
+
+        await confirmTransaction(connection, transactionSignature);
+        return transactionSignature;
+    };
+
+    const raiseDisputeTx = async (signer: Keypair, offerAddress: PublicKey): Promise<void> => {
+        const transactionSignature = await program.methods
+            .raiseDispute()
+            .accounts({
+                arbiter: signer.publicKey,
+                offer: offerAddress,
+            })
+            .signers([signer])
+            .rpc();
+
+        await confirmTransaction(connection, transactionSignature);
+    };
+
+    const resolveDisputeTx = async (
+        signer: Keypair,
+        offerAddress: PublicKey,
+        taker: PublicKey,
+        resolution: { toMaker: {} } | { toTaker: {} } | { split: { makerAmount: BN } }
+    ): Promise<void> => {
+        const transactionSignature = await program.methods
+            .resolveDispute(resolution as any)
+            .accounts({
+                arbiter: signer.publicKey,
+                maker: alice.publicKey,
+                taker,
+                tokenMintA: usdcMint.publicKey,
+                tokenMintB: wifMint.publicKey,
+                offer: offerAddress,
+                makerTokenAccountB: aliceWifAccount,
+                tokenProgram: TOKEN_PROGRAM,
+            } as any)
+            .signers([signer])
+            .rpc();
+
+        await confirmTransaction(connection, transactionSignature);
+    };
+
+    const makeArbitratedOfferTx = () =>
+        makeOfferTx(
+            alice,
+            null,
+            usdcMint.publicKey,
+            new BN(1_000_000),
+            wifMint.publicKey,
+            new BN(2_000_000),
+            TAKER_PAYS_RENT,
+            null,
+            arbiter.publicKey
+        );
+
+    const paymentVaultAddressFor = (offerAddress: PublicKey): PublicKey =>
+        getAssociatedTokenAddressSync(wifMint.publicKey, offerAddress, true, TOKEN_PROGRAM);
+
+    test("Vested offers cannot have an arbiter", async () => {
+        await expect(
+            makeOfferTx(
+                alice,
+                null,
+                usdcMint.publicKey,
+                new BN(1_000_000),
+                wifMint.publicKey,
+                new BN(2_000_000),
+                TAKER_PAYS_RENT,
+                { cliffSeconds: new BN(0), durationSeconds: new BN(60) },
+                arbiter.publicKey
+            )
+        ).rejects.toThrow(/VestingWithArbiter/);
+    });
+
+    test("Only offers with an arbiter can be accepted", async () => {
+        const { offerAddress } = await makeOfferTx(
+            alice,
+            null,
+            usdcMint.publicKey,
+            new BN(1_000_000),
+            wifMint.publicKey,
+            new BN(2_000_000)
+        );
+
+        await expect(
+            acceptOfferTx(bob, offerAddress, new BN(2_000_000))
+        ).rejects.toThrow(/NoArbiter/);
+    });
+
+    test("Offers with an arbiter cannot be taken directly", async () => {
+        const { offerAddress } = await makeArbitratedOfferTx();
+
+        await expect(
+            takeOfferTx(offerAddress, bob, new BN(2_000_000), new BN(1_000_000))
+        ).rejects.toThrow(/OfferHasArbiter/);
+    });
+
+    test("Bob accepts an arbitrated offer, then completes it", async () => {
+        const getTokenBalance = getTokenBalanceOn(connection);
+
+        const { offerAddress, vaultAddress } = await makeArbitratedOfferTx();
+
+        await expect(
+            acceptOfferTx(bob, offerAddress, new BN(1_000_000))
+        ).rejects.toThrow(/OfferTermsChanged/);
+
+        const aliceWifBefore = await getTokenBalance(aliceWifAccount);
+        const bobWifBefore = await getTokenBalance(bobWifAccount);
+        const bobUsdcBefore = await getTokenBalance(bobUsdcAccount);
+
+        await acceptOfferTx(bob, offerAddress, new BN(2_000_000));
+
+        // Bob's payment is locked, not yet with Alice.
+        expect(await getTokenBalance(bobWifAccount)).toEqual(bobWifBefore.sub(new BN(2_000_000)));
+        expect(await getTokenBalance(paymentVaultAddressFor(offerAddress))).toEqual(
+            new BN(2_000_000)
+        );
+        expect(await getTokenBalance(aliceWifAccount)).toEqual(aliceWifBefore);
+
+        await expect(completeOfferTx(arbiter, offerAddress)).rejects.toThrow(/NotTaker/);
+
+        await completeOfferTx(bob, offerAddress);
+
+        expect(await getTokenBalance(aliceWifAccount)).toEqual(aliceWifBefore.add(new BN(2_000_000)));
+        expect(await getTokenBalance(bobUsdcAccount)).toEqual(bobUsdcBefore.add(new BN(1_000_000)));
+        expect(await connection.getAccountInfo(offerAddress)).toBeNull();
+        expect(await connection.getAccountInfo(vaultAddress)).toBeNull();
+        expect(await connection.getAccountInfo(paymentVaultAddressFor(offerAddress))).toBeNull();
+    });
+
+    test("Disputed offer can neither be taken, closed nor completed", async () => {
+        const { offerAddress } = await makeArbitratedOfferTx();
+
+        await expect(raiseDisputeTx(arbiter, offerAddress)).rejects.toThrow(/OfferNotAccepted/);
+
+        await acceptOfferTx(bob, offerAddress, new BN(2_000_000));
+
+        await expect(raiseDisputeTx(bob, offerAddress)).rejects.toThrow(/NotArbiter/);
+
+        await raiseDisputeTx(arbiter, offerAddress);
+
+        await expect(
+            takeOfferTx(offerAddress, bob, new BN(2_000_000), new BN(1_000_000))
+        ).rejects.toThrow(/OfferNotOpen/);
+        await expect(
+            closeOfferTx(alice, offerAddress, usdcMint.publicKey)
+        ).rejects.toThrow(/OfferNotOpen/);
+        await expect(completeOfferTx(bob, offerAddress)).rejects.toThrow(/OfferNotAccepted/);
+    });
+
+    test("Arbiter splits a disputed offer between Alice and Bob", async () => {
+        const getTokenBalance = getTokenBalanceOn(connection);
+
+        const { offerAddress, vaultAddress } = await makeArbitratedOfferTx();
+
+        const bobWifBefore = await getTokenBalance(bobWifAccount);
+
+        await acceptOfferTx(bob, offerAddress, new BN(2_000_000));
+
+        await expect(
+            resolveDisputeTx(arbiter, offerAddress, bob.publicKey, { toMaker: {} })
+        ).rejects.toThrow(/OfferNotDisputed/);
+
+        await raiseDisputeTx(arbiter, offerAddress);
+
+        const aliceUsdcBefore = await getTokenBalance(aliceUsdcAccount);
+        const aliceWifBefore = await getTokenBalance(aliceWifAccount);
+        const bobUsdcBefore = await getTokenBalance(bobUsdcAccount);
+
+        await expect(
+            resolveDisputeTx(bob, offerAddress, bob.publicKey, { toTaker: {} })
+        ).rejects.toThrow(/NotArbiter/);
+        await expect(
+            resolveDisputeTx(arbiter, offerAddress, arbiter.publicKey, { toTaker: {} })
+        ).rejects.toThrow(/NotTaker/);
+
+        await resolveDisputeTx(arbiter, offerAddress, bob.publicKey, {
+            split: { makerAmount: new BN(400_000) },
+        });
+
+        // Bob keeps 60% of the USDC and pays 60% of the WIF, the rest of his
+        // payment comes back to him.
+        expect(await getTokenBalance(aliceUsdcAccount)).toEqual(
+            aliceUsdcBefore.add(new BN(400_000))
+        );
+        expect(await getTokenBalance(bobUsdcAccount)).toEqual(
+            bobUsdcBefore.add(new BN(600_000))
+        );
+        expect(await getTokenBalance(aliceWifAccount)).toEqual(
+            aliceWifBefore.add(new BN(1_200_000))
+        );
+        expect(await getTokenBalance(bobWifAccount)).toEqual(
+            bobWifBefore.sub(new BN(1_200_000))
+        );
+        expect(await connection.getAccountInfo(offerAddress)).toBeNull();
+        expect(await connection.getAccountInfo(vaultAddress)).toBeNull();
+        expect(await connection.getAccountInfo(paymentVaultAddressFor(offerAddress))).toBeNull();
+    });
+
+    test("Rent policy applies when an arbitrated offer is completed", async () => {
+        const { maker: carol, wifAccount: carolWifAccount } = await fundUsdcOnlyMaker();
+
+        const { offerAddress, vaultAddress } = await makeOfferTx(
+            carol,
+            null,
+            usdcMint.publicKey,
+            new BN(1_000_000),
+            wifMint.publicKey,
+            new BN(2_000_000),
+            { makerAtaPayer: { maker: {} }, vaultRentRecipient: { taker: {} } },
+            null,
+            arbiter.publicKey
+        );
+        await acceptOfferTx(bob, offerAddress, new BN(2_000_000));
+
+        const offerLamports = await connection.getBalance(offerAddress);
+        const vaultLamports = await connection.getBalance(vaultAddress);
+        const paymentVaultLamports = await connection.getBalance(
+            paymentVaultAddressFor(offerAddress)
+        );
+        const carolLamportsBefore = await connection.getBalance(carol.publicKey);
+        const bobLamportsBefore = await connection.getBalance(bob.publicKey);
+
+        const fee = await transactionFee(await completeOfferTx(bob, offerAddress));
+
+        const carolWifLamports = await connection.getBalance(carolWifAccount);
+        expect(carolWifLamports).toBeGreaterThan(0);
+
+        // Bob is reimbursed for Carol's new ATA and gets both vaults' rent.
+        expect(await connection.getBalance(bob.publicKey)).toEqual(
+            bobLamportsBefore - fee + paymentVaultLamports + vaultLamports
+        );
+        // Carol gets the offer rent back, minus her new ATA.
+        expect(await connection.getBalance(carol.publicKey)).toEqual(
+            carolLamportsBefore + offerLamports - carolWifLamports
+        );
+    });
+
+    test("Arbiter returns a disputed offer to Alice", async () => {
+        const getTokenBalance = getTokenBalanceOn(connection);
+
+        const { offerAddress, vaultAddress } = await makeArbitratedOfferTx();
+
+        const aliceUsdcBefore = await getTokenBalance(aliceUsdcAccount);
+        const aliceWifBefore = await getTokenBalance(aliceWifAccount);
+        const bobUsdcBefore = await getTokenBalance(bobUsdcAccount);
+        const bobWifBefore = await getTokenBalance(bobWifAccount);
+
+        await acceptOfferTx(bob, offerAddress, new BN(2_000_000));
+        await raiseDisputeTx(arbiter, offerAddress);
+        await resolveDisputeTx(arbiter, offerAddress, bob.publicKey, { toMaker: {} });
+
+        // Nobody trades: Alice's USDC and Bob's WIF go back where they came from.
+        expect(await getTokenBalance(aliceUsdcAccount)).toEqual(
+            aliceUsdcBefore.add(new BN(1_000_000))
+        );
+        expect(await getTokenBalance(aliceWifAccount)).toEqual(aliceWifBefore);
+        expect(await getTokenBalance(bobUsdcAccount)).toEqual(bobUsdcBefore);
+        expect(await getTokenBalance(bobWifAccount)).toEqual(bobWifBefore);
+        expect(await connection.getAccountInfo(offerAddress)).toBeNull();
+        expect(await connection.getAccountInfo(vaultAddress)).toBeNull();
+        expect(await connection.getAccountInfo(paymentVaultAddressFor(offerAddress))).toBeNull();
+    });
+
+    test("Arbiter lets a disputed trade go through to Bob", async () => {
+        const getTokenBalance = getTokenBalanceOn(connection);
+
+        const { offerAddress, vaultAddress } = await makeArbitratedOfferTx();
+
+        const aliceUsdcBefore = await getTokenBalance(aliceUsdcAccount);
+        const aliceWifBefore = await getTokenBalance(aliceWifAccount);
+        const bobUsdcBefore = await getTokenBalance(bobUsdcAccount);
+        const bobWifBefore = await getTokenBalance(bobWifAccount);
+
+        await acceptOfferTx(bob, offerAddress, new BN(2_000_000));
+        await raiseDisputeTx(arbiter, offerAddress);
+        await resolveDisputeTx(arbiter, offerAddress, bob.publicKey, { toTaker: {} });
+
+        expect(await getTokenBalance(aliceUsdcAccount)).toEqual(aliceUsdcBefore);
+        expect(await getTokenBalance(aliceWifAccount)).toEqual(
+            aliceWifBefore.add(new BN(2_000_000))
+        );
+        expect(await getTokenBalance(bobUsdcAccount)).toEqual(
+            bobUsdcBefore.add(new BN(1_000_000))
+        );
+        expect(await getTokenBalance(bobWifAccount)).toEqual(
+            bobWifBefore.sub(new BN(2_000_000))
+        );
+        expect(await connection.getAccountInfo(offerAddress)).toBeNull();
+        expect(await connection.getAccountInfo(vaultAddress)).toBeNull();
+        expect(await connection.getAccountInfo(paymentVaultAddressFor(offerAddress))).toBeNull();
+    });
+
+});
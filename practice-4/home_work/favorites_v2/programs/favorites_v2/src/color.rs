@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::ErrorCode;
+
+pub const MAX_COLOR_LEN: usize = 50;
+
+/// Names accepted by `normalize_color`: the CSS 2.1 basic colors plus a few
+/// common ones.
+pub const COLOR_NAMES: [&str; 25] = [
+    "aqua", "black", "blue", "brown", "cyan", "fuchsia", "gold", "gray", "green", "grey", "indigo",
+    "lime", "magenta", "maroon", "navy", "olive", "orange", "pink", "purple", "red", "silver",
+    "teal", "violet", "white", "yellow",
+];
+
+/// Checks `color` fits in `Favorites::color`, normalizing it first when the
+/// caller opted in. Otherwise the color is stored exactly as given.
+pub fn prepare_color(color: String, normalize: bool) -> Result<String> {
+    let color = if normalize {
+        normalize_color(&color)?
+    } else {
+        color
+    };
+    require!(color.len() <= MAX_COLOR_LEN, ErrorCode::ColorTooLong);
+    Ok(color)
+}
+
+/// Trims and lower-cases a color, expanding `#RGB` to `#rrggbb`. Anything not
+/// starting with `#` must be one of `COLOR_NAMES`.
+fn normalize_color(color: &str) -> Result<String> {
+    let color = color.trim().to_lowercase();
+
+    let color = match color.strip_prefix('#') {
+        Some(hex) => {
+            require!(
+                (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+                ErrorCode::InvalidColor
+            );
+            if hex.len() == 3 {
+                hex.chars().fold(String::from("#"), |mut expanded, c| {
+                    expanded.push(c);
+                    expanded.push(c);
+                    expanded
+                })
+            } else {
+                color
+            }
+        }
+        None => {
+            require!(!color.is_empty(), ErrorCode::InvalidColor);
+            require!(
+                COLOR_NAMES.contains(&color.as_str()),
+                ErrorCode::UnknownColorName
+            );
+            color
+        }
+    };
+    Ok(color)
+}
@@ -4,4 +4,42 @@ use anchor_lang::prelude::*;
 pub enum ErrorCode {
     #[msg("Only the owner or authority can perform this action!")]
     Unauthorized,
-} 
\ No newline at end of file
+    #[msg("Signer is not the pending authority!")]
+    NotPendingAuthority,
+    #[msg("There is no pending authority transfer!")]
+    NoPendingAuthority,
+    #[msg("set_authority can only revoke, use propose_authority to hand over!")]
+    AuthorityNeedsAcceptance,
+    #[msg("Only the owner can overwrite favorites!")]
+    NotOwner,
+    #[msg("Color is longer than 50 bytes!")]
+    ColorTooLong,
+    #[msg("Color must be a name or a #RGB / #RRGGBB hex value!")]
+    InvalidColor,
+    #[msg("Favorites account is already on the latest layout!")]
+    AlreadyMigrated,
+    #[msg("Favorites account is on an outdated layout, call migrate_favorites first!")]
+    OutdatedFavorites,
+    #[msg("Account is not a favorites account of the practice-3 program!")]
+    NotLegacyFavorites,
+    #[msg("Entry key must be between 1 and 32 bytes!")]
+    EntryKeyTooLong,
+    #[msg("Entry value is longer than 64 bytes!")]
+    EntryValueTooLong,
+    #[msg("Favorites can't hold more than 16 entries!")]
+    TooManyEntries,
+    #[msg("No entry with this key!")]
+    EntryNotFound,
+    #[msg("Permissions must be a non-empty combination of the PERMISSION_* bits!")]
+    InvalidPermissions,
+    #[msg("Favorites can't have more than 4 delegates!")]
+    TooManyDelegates,
+    #[msg("No delegate with this key!")]
+    DelegateNotFound,
+    #[msg("Favorites are too large to return, fetch the account instead!")]
+    ReturnDataTooLarge,
+    #[msg("History is enabled for these favorites, pass the history account!")]
+    HistoryAccountMissing,
+    #[msg("Unknown color name, use a hex value instead!")]
+    UnknownColorName,
+}
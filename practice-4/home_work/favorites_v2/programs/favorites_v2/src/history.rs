@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+use crate::{color::MAX_COLOR_LEN, errors::ErrorCode, Favorites};
+
+pub const HISTORY_LEN: usize = 8;
+
+/// Snapshot of the fields after a change; diff consecutive records to see what changed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ChangeRecord {
+    pub timestamp: i64,
+    pub signer: Pubkey,
+    pub number: u64,
+    #[max_len(MAX_COLOR_LEN)]
+    pub color: String,
+    pub authority: Option<Pubkey>,
+}
+
+/// Ring buffer of the last `HISTORY_LEN` changes to the `number`, `color` or
+/// `authority` of a `Favorites` account, the fields a `ChangeRecord` holds.
+/// Changes to entries and delegates, and authority transfers that are only
+/// proposed or cancelled, are not recorded.
+#[account]
+#[derive(InitSpace)]
+pub struct FavoritesHistory {
+    pub favorites: Pubkey,
+    /// Slot in `records` the next change overwrites once the buffer is full.
+    pub next_index: u8,
+    #[max_len(HISTORY_LEN)]
+    pub records: Vec<ChangeRecord>,
+    pub bump: u8,
+}
+
+impl FavoritesHistory {
+    pub fn record(&mut self, favorites: &Favorites, signer: Pubkey) -> Result<()> {
+        let record = ChangeRecord {
+            timestamp: Clock::get()?.unix_timestamp,
+            signer,
+            number: favorites.number,
+            color: favorites.color.clone(),
+            authority: favorites.authority,
+        };
+
+        if self.records.len() < HISTORY_LEN {
+            self.records.push(record);
+        } else {
+            self.records[self.next_index as usize] = record;
+        }
+        self.next_index = ((self.next_index as usize + 1) % HISTORY_LEN) as u8;
+        Ok(())
+    }
+}
+
+/// Appends to the history, which must be passed in once it's enabled.
+pub fn record_change(
+    history: &mut Option<Account<FavoritesHistory>>,
+    favorites: &Favorites,
+    signer: Pubkey,
+) -> Result<()> {
+    match history {
+        Some(history) => history.record(favorites, signer),
+        None if favorites.history_enabled => err!(ErrorCode::HistoryAccountMissing),
+        None => Ok(()),
+    }
+}
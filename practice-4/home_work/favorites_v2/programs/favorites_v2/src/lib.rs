@@ -1,55 +1,243 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::program::MAX_RETURN_DATA};
 
+mod color;
 mod errors;
+mod history;
+mod migration;
+use color::{prepare_color, MAX_COLOR_LEN};
 use errors::ErrorCode;
+use history::record_change;
+pub use history::{ChangeRecord, FavoritesHistory, HISTORY_LEN};
 
 declare_id!("646xu1j5tJ56w1hiPmdjHo2KV4hXg4kCC7Xr3aRmDU4V");
 
 pub const ANCHOR_DISCRIMINATOR_SIZE: usize = 8;
+/// The practice-3 `favorites` program, whose accounts `import_legacy_favorites` reads.
+pub const LEGACY_FAVORITES_PROGRAM_ID: Pubkey =
+    pubkey!("329AjHWJsuehP7qqhGrte4FSHng5hEkyife64j2haZnj");
+/// Bump whenever `Favorites` changes layout, and teach `migrate_favorites` the old one.
+pub const FAVORITES_VERSION: u8 = 4;
+pub const MAX_DELEGATES: usize = 4;
+pub const MAX_ENTRIES: usize = 16;
+pub const MAX_ENTRY_KEY_LEN: usize = 32;
+pub const MAX_ENTRY_VALUE_LEN: usize = 64;
+
+/// Permission bits a delegate can be granted, one per field it may change.
+pub const PERMISSION_NUMBER: u8 = 1 << 0;
+pub const PERMISSION_COLOR: u8 = 1 << 1;
+pub const PERMISSION_ENTRIES: u8 = 1 << 2;
+pub const PERMISSION_ALL: u8 = PERMISSION_NUMBER | PERMISSION_COLOR | PERMISSION_ENTRIES;
 
 #[account]
 #[derive(InitSpace)]
 pub struct Favorites {
+    pub version: u8,
+    pub owner: Pubkey,
     pub number: u64,
-    #[max_len(50)]
+    #[max_len(MAX_COLOR_LEN)]
     pub color: String,
     pub authority: Option<Pubkey>,
+    pub pending_authority: Option<Pubkey>,
+    /// Whether the authority may close the account as well as the owner.
+    pub authority_can_close: bool,
+    /// Grown and shrunk one entry at a time, see `Favorites::space`.
+    #[max_len(0)]
+    pub entries: Vec<FavoriteEntry>,
+    // Fields added in later versions go below, so older layouts stay a prefix.
+    #[max_len(MAX_DELEGATES)]
+    pub delegates: Vec<Delegate>,
+    /// Set by `enable_history`; from then on changes must pass the history account.
+    pub history_enabled: bool,
+}
+
+/// Key allowed to change only the fields in `permissions` (see `PERMISSION_*`).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Delegate {
+    pub key: Pubkey,
+    pub permissions: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct FavoriteEntry {
+    #[max_len(MAX_ENTRY_KEY_LEN)]
+    pub key: String,
+    #[max_len(MAX_ENTRY_VALUE_LEN)]
+    pub value: String,
+}
+
+impl Favorites {
+    /// Account size for `entries` entries, each sized for its longest key and value.
+    pub fn space(entries: usize) -> usize {
+        ANCHOR_DISCRIMINATOR_SIZE + Favorites::INIT_SPACE + entries * FavoriteEntry::INIT_SPACE
+    }
+
+    /// The authority (or the owner when there is none) may change anything,
+    /// delegates only what their permission bits allow.
+    pub fn check_permissions(&self, user: Pubkey, required: u8) -> Result<()> {
+        if user == self.authority.unwrap_or(self.owner) {
+            return Ok(());
+        }
+        let Some(delegate) = self.delegates.iter().find(|delegate| delegate.key == user) else {
+            msg!("{} is neither the authority nor a delegate", user);
+            return err!(ErrorCode::Unauthorized);
+        };
+        let missing = required & !delegate.permissions;
+        if missing != 0 {
+            msg!(
+                "Delegate {} lacks permissions {:#05b} (has {:#05b})",
+                user,
+                missing,
+                delegate.permissions
+            );
+            return err!(ErrorCode::Unauthorized);
+        }
+        Ok(())
+    }
+
+    pub fn entry_count_with(&self, key: &str) -> usize {
+        let is_new = !self.entries.iter().any(|entry| entry.key == key);
+        self.entries.len() + usize::from(is_new)
+    }
+
+    pub fn entry_count_without(&self, key: &str) -> usize {
+        let is_present = self.entries.iter().any(|entry| entry.key == key);
+        self.entries.len() - usize::from(is_present)
+    }
+}
+
+#[event]
+pub struct FavoritesSet {
+    pub favorites: Pubkey,
+    pub signer: Pubkey,
+    /// `None` when the account was just created.
+    pub old_number: Option<u64>,
+    pub old_color: Option<String>,
+    pub new_number: u64,
+    pub new_color: String,
+}
+
+#[event]
+pub struct FavoritesUpdated {
+    pub favorites: Pubkey,
+    pub signer: Pubkey,
+    pub old_number: u64,
+    pub new_number: u64,
+    pub old_color: String,
+    pub new_color: String,
+}
+
+#[event]
+pub struct AuthorityChanged {
+    pub favorites: Pubkey,
+    pub signer: Pubkey,
+    pub old_authority: Option<Pubkey>,
+    pub new_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct AuthorityTransferProposed {
+    pub favorites: Pubkey,
+    pub proposed_by: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferAccepted {
+    pub favorites: Pubkey,
+    pub previous_authority: Option<Pubkey>,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferCancelled {
+    pub favorites: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub pending_authority: Pubkey,
 }
 
 #[derive(Accounts)]
-pub struct SetFavorites<'info> {
+pub struct UpsertFavorites<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
     #[account(
-        init,
+        init_if_needed,
         payer = user,
-        space = ANCHOR_DISCRIMINATOR_SIZE + Favorites::INIT_SPACE,
-        seeds = [b"favorites", user.key().as_ref()],
+        space = Favorites::space(0),
+        seeds = [b"favorites", owner.key().as_ref()],
         bump,
+        // Freshly created accounts get their version in the handler.
+        constraint = favorites.version == FAVORITES_VERSION
+            || favorites.owner == Pubkey::default() @ ErrorCode::OutdatedFavorites,
     )]
     pub favorites: Account<'info, Favorites>,
+
+    /// Required once `favorites.history_enabled` is set, see `enable_history`.
+    #[account(
+        mut,
+        seeds = [b"history", favorites.key().as_ref()],
+        bump = history.bump,
+    )]
+    pub history: Option<Account<'info, FavoritesHistory>>,
+
+    /// CHECK: This is used only for PDA derivation and authorization check
+    pub owner: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct SetFavoritesWithAuthority<'info> {
+pub struct UpdateFavorites<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
+    // Permissions depend on which fields change, so they're checked in the handler.
     #[account(
-        init,
-        payer = user,
-        space = ANCHOR_DISCRIMINATOR_SIZE + Favorites::INIT_SPACE,
-        seeds = [b"favorites", user.key().as_ref()],
+        mut,
+        seeds = [b"favorites", owner.key().as_ref()],
+        bump,
+        constraint = favorites.version == FAVORITES_VERSION @ ErrorCode::OutdatedFavorites,
+        has_one = owner @ ErrorCode::Unauthorized,
+    )]
+    pub favorites: Account<'info, Favorites>,
+
+    /// Required once `favorites.history_enabled` is set, see `enable_history`.
+    #[account(
+        mut,
+        seeds = [b"history", favorites.key().as_ref()],
+        bump = history.bump,
+    )]
+    pub history: Option<Account<'info, FavoritesHistory>>,
+
+    /// CHECK: This is used only for PDA derivation and authorization check
+    pub owner: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(key: String)]
+pub struct SetEntry<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"favorites", owner.key().as_ref()],
         bump,
+        constraint = favorites.version == FAVORITES_VERSION @ ErrorCode::OutdatedFavorites,
+        has_one = owner @ ErrorCode::Unauthorized,
+        realloc = Favorites::space(favorites.entry_count_with(&key)),
+        realloc::payer = user,
+        realloc::zero = false,
     )]
     pub favorites: Account<'info, Favorites>,
+
+    /// CHECK: This is used only for PDA derivation and authorization check
+    pub owner: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateFavorites<'info> {
+#[instruction(key: String)]
+pub struct RemoveEntry<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
@@ -57,12 +245,17 @@ pub struct UpdateFavorites<'info> {
         mut,
         seeds = [b"favorites", owner.key().as_ref()],
         bump,
-        constraint = user.key() == favorites.authority.unwrap_or(owner.key()) @ ErrorCode::Unauthorized
+        constraint = favorites.version == FAVORITES_VERSION @ ErrorCode::OutdatedFavorites,
+        has_one = owner @ ErrorCode::Unauthorized,
+        realloc = Favorites::space(favorites.entry_count_without(&key)),
+        realloc::payer = user,
+        realloc::zero = false,
     )]
     pub favorites: Account<'info, Favorites>,
 
     /// CHECK: This is used only for PDA derivation and authorization check
     pub owner: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -72,41 +265,202 @@ pub struct SetAuthority<'info> {
 
     #[account(
         mut,
-        seeds = [b"favorites", user.key().as_ref()],
+        seeds = [b"favorites", owner.key().as_ref()],
+        bump,
+        constraint = favorites.version == FAVORITES_VERSION @ ErrorCode::OutdatedFavorites,
+        has_one = owner @ ErrorCode::Unauthorized,
+        constraint = user.key() == owner.key() || favorites.authority == Some(user.key()) @ ErrorCode::Unauthorized
+    )]
+    pub favorites: Account<'info, Favorites>,
+
+    /// Required once `favorites.history_enabled` is set, see `enable_history`.
+    #[account(
+        mut,
+        seeds = [b"history", favorites.key().as_ref()],
+        bump = history.bump,
+    )]
+    pub history: Option<Account<'info, FavoritesHistory>>,
+
+    /// CHECK: This is used only for PDA derivation and authorization check
+    pub owner: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub pending_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"favorites", owner.key().as_ref()],
+        bump,
+        constraint = favorites.version == FAVORITES_VERSION @ ErrorCode::OutdatedFavorites,
+        has_one = owner @ ErrorCode::Unauthorized,
+        constraint = favorites.pending_authority == Some(pending_authority.key()) @ ErrorCode::NotPendingAuthority
+    )]
+    pub favorites: Account<'info, Favorites>,
+
+    /// Required once `favorites.history_enabled` is set, see `enable_history`.
+    #[account(
+        mut,
+        seeds = [b"history", favorites.key().as_ref()],
+        bump = history.bump,
+    )]
+    pub history: Option<Account<'info, FavoritesHistory>>,
+
+    /// CHECK: This is used only for PDA derivation and authorization check
+    pub owner: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAuthorityCanClose<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"favorites", owner.key().as_ref()],
+        bump,
+        constraint = favorites.version == FAVORITES_VERSION @ ErrorCode::OutdatedFavorites,
+        has_one = owner @ ErrorCode::Unauthorized,
+    )]
+    pub favorites: Account<'info, Favorites>,
+}
+
+#[derive(Accounts)]
+pub struct CloseFavorites<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        close = recipient,
+        seeds = [b"favorites", owner.key().as_ref()],
+        bump,
+        constraint = favorites.version == FAVORITES_VERSION @ ErrorCode::OutdatedFavorites,
+        has_one = owner @ ErrorCode::Unauthorized,
+        constraint = user.key() == owner.key()
+            || (favorites.authority_can_close && favorites.authority == Some(user.key()))
+            @ ErrorCode::Unauthorized
+    )]
+    pub favorites: Account<'info, Favorites>,
+
+    /// Required once `favorites.history_enabled` is set: it is closed along
+    /// with the favorites, so `enable_history` can create it again later.
+    #[account(
+        mut,
+        close = recipient,
+        seeds = [b"history", favorites.key().as_ref()],
+        bump = history.bump,
+    )]
+    pub history: Option<Account<'info, FavoritesHistory>>,
+
+    /// CHECK: This is used only for PDA derivation and authorization check
+    pub owner: AccountInfo<'info>,
+
+    /// Receives the rent of the closed accounts.
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetFavorites<'info> {
+    #[account(
+        seeds = [b"favorites", owner.key().as_ref()],
+        bump,
+        constraint = favorites.version == FAVORITES_VERSION @ ErrorCode::OutdatedFavorites,
+    )]
+    pub favorites: Account<'info, Favorites>,
+
+    /// CHECK: This is used only for PDA derivation
+    pub owner: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EnableHistory<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"favorites", owner.key().as_ref()],
+        bump,
+        constraint = favorites.version == FAVORITES_VERSION @ ErrorCode::OutdatedFavorites,
+        has_one = owner @ ErrorCode::Unauthorized,
+    )]
+    pub favorites: Account<'info, Favorites>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = ANCHOR_DISCRIMINATOR_SIZE + FavoritesHistory::INIT_SPACE,
+        seeds = [b"history", favorites.key().as_ref()],
+        bump,
+    )]
+    pub history: Account<'info, FavoritesHistory>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateFavorites<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Outdated accounts can't be deserialized as `Favorites`, the layout is checked in the handler
+    #[account(
+        mut,
+        seeds = [b"favorites", owner.key().as_ref()],
+        bump,
+        owner = crate::ID,
+    )]
+    pub favorites: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ImportLegacyFavorites<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Owned by the practice-3 program, the layout is checked in the handler
+    #[account(
+        seeds = [b"favorites", owner.key().as_ref()],
+        bump,
+        seeds::program = LEGACY_FAVORITES_PROGRAM_ID,
+        owner = LEGACY_FAVORITES_PROGRAM_ID,
+    )]
+    pub legacy_favorites: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Favorites::space(0),
+        seeds = [b"favorites", owner.key().as_ref()],
         bump,
-        constraint = user.key() == favorites.to_account_info().key() @ ErrorCode::Unauthorized
     )]
     pub favorites: Account<'info, Favorites>,
+    pub system_program: Program<'info, System>,
 }
 
 #[program]
 pub mod favorites {
     use super::*;
-    pub fn set_favorites(context: Context<SetFavorites>, number: u64, color: String) -> Result<()> {
-        let user_public_key = context.accounts.user.key();
-        msg!("Greetings from {}", context.program_id);
-        msg!(
-            "User {}'s favorite number is {} and favorite color is: {}",
-            user_public_key,
-            number,
-            color
-        );
-
-        context.accounts.favorites.set_inner(Favorites { 
-            number, 
-            color,
-            authority: None,
-        });
-        Ok(())
-    }
-
-    pub fn set_favorites_with_authority(
-        context: Context<SetFavoritesWithAuthority>, 
-        number: u64, 
+    // Creates the favorites on first call and overwrites `number` and `color`
+    // afterwards. A new `authority` is only proposed, as with
+    // `propose_authority`, and takes over once it signs `accept_authority`.
+    // `normalize_color` opts into trimming, lower-casing and expanding `#RGB`.
+    pub fn upsert_favorites(
+        context: Context<UpsertFavorites>,
+        number: u64,
         color: String,
-        authority: Option<Pubkey>
+        authority: Option<Pubkey>,
+        normalize_color: bool,
     ) -> Result<()> {
         let user_public_key = context.accounts.user.key();
+        require_keys_eq!(
+            user_public_key,
+            context.accounts.owner.key(),
+            ErrorCode::NotOwner
+        );
+        let color = prepare_color(color, normalize_color)?;
+        msg!("Greetings from {}", context.program_id);
         msg!(
             "Setting preferences for {}: number={}, color={}, authority={:?}",
             user_public_key,
@@ -115,23 +469,66 @@ pub mod favorites {
             authority
         );
 
-        context.accounts.favorites.set_inner(Favorites { 
-            number, 
-            color,
-            authority,
+        let favorites = &mut context.accounts.favorites;
+        let is_new = favorites.owner == Pubkey::default();
+        if is_new {
+            favorites.version = FAVORITES_VERSION;
+            favorites.owner = user_public_key;
+        }
+        let old_number = std::mem::replace(&mut favorites.number, number);
+        let old_color = std::mem::replace(&mut favorites.color, color);
+        emit!(FavoritesSet {
+            favorites: favorites.key(),
+            signer: user_public_key,
+            old_number: (!is_new).then_some(old_number),
+            old_color: (!is_new).then_some(old_color),
+            new_number: favorites.number,
+            new_color: favorites.color.clone(),
         });
-        Ok(())
+
+        if let Some(new_authority) = authority.filter(|key| Some(*key) != favorites.authority) {
+            favorites.pending_authority = Some(new_authority);
+            emit!(AuthorityTransferProposed {
+                favorites: favorites.key(),
+                proposed_by: user_public_key,
+                pending_authority: new_authority,
+            });
+        }
+        record_change(&mut context.accounts.history, favorites, user_public_key)
+    }
+
+    // Read-only view: Anchor hands the returned value back through return
+    // data, so other programs can CPI into it and clients can simulate it.
+    // Return data is capped at 1024 bytes, which a full set of entries exceeds.
+    pub fn get_favorites(context: Context<GetFavorites>) -> Result<Favorites> {
+        let favorites = context.accounts.favorites.clone().into_inner();
+        require!(
+            favorites.try_to_vec()?.len() <= MAX_RETURN_DATA,
+            ErrorCode::ReturnDataTooLarge
+        );
+        Ok(favorites)
     }
 
     pub fn update_favorites(
         context: Context<UpdateFavorites>,
         number: Option<u64>,
         color: Option<String>,
+        normalize_color: bool,
     ) -> Result<()> {
         let user_public_key = context.accounts.user.key();
         msg!("Updating favorites for user {}", user_public_key);
 
         let favorites = &mut context.accounts.favorites;
+        let mut required = 0;
+        if number.is_some() {
+            required |= PERMISSION_NUMBER;
+        }
+        if color.is_some() {
+            required |= PERMISSION_COLOR;
+        }
+        favorites.check_permissions(user_public_key, required)?;
+        let old_number = favorites.number;
+        let old_color = favorites.color.clone();
 
         if let Some(new_number) = number {
             msg!("Updating favorite number to: {}", new_number);
@@ -139,20 +536,253 @@ pub mod favorites {
         }
 
         if let Some(new_color) = color {
+            let new_color = prepare_color(new_color, normalize_color)?;
             msg!("Updating favorite color to: {}", new_color);
             favorites.color = new_color;
         }
 
+        emit!(FavoritesUpdated {
+            favorites: favorites.key(),
+            signer: user_public_key,
+            old_number,
+            new_number: favorites.number,
+            old_color,
+            new_color: favorites.color.clone(),
+        });
+        record_change(&mut context.accounts.history, favorites, user_public_key)
+    }
+
+    // Adds or overwrites a key/value entry. The account grows by one entry
+    // for a new key, with the user paying the extra rent.
+    pub fn set_entry(context: Context<SetEntry>, key: String, value: String) -> Result<()> {
+        require!(
+            !key.is_empty() && key.len() <= MAX_ENTRY_KEY_LEN,
+            ErrorCode::EntryKeyTooLong
+        );
+        require!(
+            value.len() <= MAX_ENTRY_VALUE_LEN,
+            ErrorCode::EntryValueTooLong
+        );
+        msg!("Setting favorite {} to: {}", key, value);
+
+        let favorites = &mut context.accounts.favorites;
+        favorites.check_permissions(context.accounts.user.key(), PERMISSION_ENTRIES)?;
+        match favorites.entries.iter_mut().find(|entry| entry.key == key) {
+            Some(entry) => entry.value = value,
+            None => {
+                require!(
+                    favorites.entries.len() < MAX_ENTRIES,
+                    ErrorCode::TooManyEntries
+                );
+                favorites.entries.push(FavoriteEntry { key, value });
+            }
+        }
         Ok(())
     }
 
+    // Removes an entry and shrinks the account, refunding the rent to the user.
+    pub fn remove_entry(context: Context<RemoveEntry>, key: String) -> Result<()> {
+        msg!("Removing favorite {}", key);
+
+        let favorites = &mut context.accounts.favorites;
+        favorites.check_permissions(context.accounts.user.key(), PERMISSION_ENTRIES)?;
+        let Some(index) = favorites.entries.iter().position(|entry| entry.key == key) else {
+            return err!(ErrorCode::EntryNotFound);
+        };
+        favorites.entries.remove(index);
+        Ok(())
+    }
+
+    // Only revokes the authority: handing it to a new key goes through
+    // `propose_authority` and `accept_authority`.
     pub fn set_authority(
         context: Context<SetAuthority>,
         new_authority: Option<Pubkey>,
     ) -> Result<()> {
+        require!(new_authority.is_none(), ErrorCode::AuthorityNeedsAcceptance);
         let favorites = &mut context.accounts.favorites;
         msg!("Setting new authority: {:?}",new_authority);
+        emit!(AuthorityChanged {
+            favorites: favorites.key(),
+            signer: context.accounts.user.key(),
+            old_authority: favorites.authority,
+            new_authority,
+        });
         favorites.authority = new_authority;
+        favorites.pending_authority = None;
+        record_change(
+            &mut context.accounts.history,
+            favorites,
+            context.accounts.user.key(),
+        )
+    }
+
+    // Grants `delegate` the given `PERMISSION_*` bits, replacing any it had.
+    pub fn add_delegate(
+        context: Context<SetAuthority>,
+        delegate: Pubkey,
+        permissions: u8,
+    ) -> Result<()> {
+        require!(
+            permissions != 0 && permissions & !PERMISSION_ALL == 0,
+            ErrorCode::InvalidPermissions
+        );
+        msg!("Delegating {:#05b} to {}", permissions, delegate);
+
+        let favorites = &mut context.accounts.favorites;
+        match favorites
+            .delegates
+            .iter_mut()
+            .find(|existing| existing.key == delegate)
+        {
+            Some(existing) => existing.permissions = permissions,
+            None => {
+                require!(
+                    favorites.delegates.len() < MAX_DELEGATES,
+                    ErrorCode::TooManyDelegates
+                );
+                favorites.delegates.push(Delegate {
+                    key: delegate,
+                    permissions,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn revoke_delegate(context: Context<SetAuthority>, delegate: Pubkey) -> Result<()> {
+        msg!("Revoking delegate {}", delegate);
+
+        let favorites = &mut context.accounts.favorites;
+        let Some(index) = favorites
+            .delegates
+            .iter()
+            .position(|existing| existing.key == delegate)
+        else {
+            return err!(ErrorCode::DelegateNotFound);
+        };
+        favorites.delegates.remove(index);
+        Ok(())
+    }
+
+    // Same accounts and checks as `set_authority`, but the new authority only
+    // takes over once it signs `accept_authority`.
+    pub fn propose_authority(context: Context<SetAuthority>, new_authority: Pubkey) -> Result<()> {
+        let favorites = &mut context.accounts.favorites;
+        msg!("Proposing new authority: {}", new_authority);
+        favorites.pending_authority = Some(new_authority);
+
+        emit!(AuthorityTransferProposed {
+            favorites: favorites.key(),
+            proposed_by: context.accounts.user.key(),
+            pending_authority: new_authority,
+        });
+        Ok(())
+    }
+
+    pub fn accept_authority(context: Context<AcceptAuthority>) -> Result<()> {
+        let favorites = &mut context.accounts.favorites;
+        let new_authority = context.accounts.pending_authority.key();
+        msg!("Accepting authority: {}", new_authority);
+
+        let previous_authority = favorites.authority;
+        favorites.authority = Some(new_authority);
+        favorites.pending_authority = None;
+
+        emit!(AuthorityTransferAccepted {
+            favorites: favorites.key(),
+            previous_authority,
+            new_authority,
+        });
+        emit!(AuthorityChanged {
+            favorites: favorites.key(),
+            signer: new_authority,
+            old_authority: previous_authority,
+            new_authority: Some(new_authority),
+        });
+        record_change(&mut context.accounts.history, favorites, new_authority)
+    }
+
+    pub fn cancel_authority_transfer(context: Context<SetAuthority>) -> Result<()> {
+        let favorites = &mut context.accounts.favorites;
+        let Some(pending_authority) = favorites.pending_authority.take() else {
+            return err!(ErrorCode::NoPendingAuthority);
+        };
+        msg!("Cancelling authority transfer to: {}", pending_authority);
+
+        emit!(AuthorityTransferCancelled {
+            favorites: favorites.key(),
+            cancelled_by: context.accounts.user.key(),
+            pending_authority,
+        });
+        Ok(())
+    }
+
+    pub fn set_authority_can_close(
+        context: Context<SetAuthorityCanClose>,
+        authority_can_close: bool,
+    ) -> Result<()> {
+        msg!("Authority can close favorites: {}", authority_can_close);
+        context.accounts.favorites.authority_can_close = authority_can_close;
+        Ok(())
+    }
+
+    // Once closed, `upsert_favorites` creates the same PDA from scratch again.
+    pub fn close_favorites(context: Context<CloseFavorites>) -> Result<()> {
+        require!(
+            context.accounts.history.is_some() || !context.accounts.favorites.history_enabled,
+            ErrorCode::HistoryAccountMissing
+        );
+        msg!(
+            "Closing favorites of {}, rent goes to {}",
+            context.accounts.owner.key(),
+            context.accounts.recipient.key()
+        );
+        Ok(())
+    }
+
+    // Creates the companion PDA keeping the last `HISTORY_LEN` changes to
+    // `number`, `color` and `authority`. From then on, instructions changing
+    // those fields are rejected unless it is passed as `history`; entries,
+    // delegates and pending authority transfers are not recorded.
+    pub fn enable_history(context: Context<EnableHistory>) -> Result<()> {
+        msg!("Enabling history for {}", context.accounts.favorites.key());
+        context.accounts.favorites.history_enabled = true;
+        context.accounts.history.set_inner(FavoritesHistory {
+            favorites: context.accounts.favorites.key(),
+            next_index: 0,
+            records: Vec::new(),
+            bump: context.bumps.history,
+        });
+        Ok(())
+    }
+
+    // Rewrites an account created by an older version of this program into
+    // the current layout. Accounts of the practice-3 program go through
+    // `import_legacy_favorites` instead.
+    pub fn migrate_favorites(context: Context<MigrateFavorites>) -> Result<()> {
+        msg!("Migrating favorites of {}", context.accounts.owner.key());
+        migration::migrate_favorites(
+            &context.accounts.favorites,
+            &context.accounts.owner,
+            &context.accounts.system_program,
+        )
+    }
+
+    // Copies the practice-3 program's account for the owner into a new account
+    // of this program. The old account stays with the practice-3 program,
+    // which can't close it, so the owner pays the rent of the new one.
+    pub fn import_legacy_favorites(context: Context<ImportLegacyFavorites>) -> Result<()> {
+        msg!(
+            "Importing favorites of {} from {}",
+            context.accounts.owner.key(),
+            context.accounts.legacy_favorites.key()
+        );
+        let favorites = migration::read_legacy_favorites(
+            &context.accounts.legacy_favorites.try_borrow_data()?,
+            context.accounts.owner.key(),
+        )?;
+        context.accounts.favorites.set_inner(favorites);
         Ok(())
     }
 }
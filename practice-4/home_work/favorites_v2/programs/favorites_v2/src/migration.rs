@@ -0,0 +1,198 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+    Discriminator,
+};
+
+use crate::{
+    color::MAX_COLOR_LEN, errors::ErrorCode, Delegate, FavoriteEntry, Favorites,
+    ANCHOR_DISCRIMINATOR_SIZE, FAVORITES_VERSION,
+};
+
+/// Layout written by the practice-3 `favorites` program.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyFavorites {
+    pub number: u64,
+    pub color: String,
+}
+
+/// Layout of this program before `Favorites` carried a version byte. Its
+/// accounts were allocated at exactly `UnversionedFavorites::SIZE` bytes,
+/// smaller than any versioned layout, which is how they are told apart: the
+/// first byte is the low byte of `number`, not a version.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct UnversionedFavorites {
+    pub number: u64,
+    #[max_len(MAX_COLOR_LEN)]
+    pub color: String,
+    pub authority: Option<Pubkey>,
+}
+
+impl UnversionedFavorites {
+    pub const SIZE: usize = ANCHOR_DISCRIMINATOR_SIZE + Self::INIT_SPACE;
+}
+
+/// Version 1 layout, before `entries` were added.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct FavoritesV1 {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub number: u64,
+    pub color: String,
+    pub authority: Option<Pubkey>,
+    pub pending_authority: Option<Pubkey>,
+    pub authority_can_close: bool,
+}
+
+/// Version 2 layout, before `delegates` were added.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct FavoritesV2 {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub number: u64,
+    pub color: String,
+    pub authority: Option<Pubkey>,
+    pub pending_authority: Option<Pubkey>,
+    pub authority_can_close: bool,
+    pub entries: Vec<FavoriteEntry>,
+}
+
+/// Version 3 layout, before `history_enabled` was added.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct FavoritesV3 {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub number: u64,
+    pub color: String,
+    pub authority: Option<Pubkey>,
+    pub pending_authority: Option<Pubkey>,
+    pub authority_can_close: bool,
+    pub entries: Vec<FavoriteEntry>,
+    pub delegates: Vec<Delegate>,
+}
+
+/// Reads an account written by the practice-3 `favorites` program. Both
+/// programs name the account `Favorites`, so they share the discriminator.
+pub fn read_legacy_favorites(data: &[u8], owner: Pubkey) -> Result<Favorites> {
+    require!(
+        data.starts_with(Favorites::DISCRIMINATOR),
+        ErrorCode::NotLegacyFavorites
+    );
+    let legacy = LegacyFavorites::deserialize(&mut &data[ANCHOR_DISCRIMINATOR_SIZE..])?;
+    Ok(Favorites {
+        version: FAVORITES_VERSION,
+        owner,
+        number: legacy.number,
+        color: legacy.color,
+        authority: None,
+        pending_authority: None,
+        authority_can_close: false,
+        entries: Vec::new(),
+        delegates: Vec::new(),
+        history_enabled: false,
+    })
+}
+
+/// Reads an older layout of this program and converts it to the current
+/// `Favorites`. `owner` is only used for the unversioned layout, which didn't
+/// store it.
+fn read_outdated_favorites(data: &[u8], owner: Pubkey) -> Result<Favorites> {
+    require!(
+        data.starts_with(Favorites::DISCRIMINATOR),
+        ErrorCode::AlreadyMigrated
+    );
+    let mut body = &data[ANCHOR_DISCRIMINATOR_SIZE..];
+
+    if data.len() == UnversionedFavorites::SIZE {
+        let unversioned = UnversionedFavorites::deserialize(&mut body)?;
+        return Ok(Favorites {
+            version: FAVORITES_VERSION,
+            owner,
+            number: unversioned.number,
+            color: unversioned.color,
+            authority: unversioned.authority,
+            pending_authority: None,
+            authority_can_close: false,
+            entries: Vec::new(),
+            delegates: Vec::new(),
+            history_enabled: false,
+        });
+    }
+
+    match body.first() {
+        Some(1) => {
+            let v1 = FavoritesV1::deserialize(&mut body)?;
+            Ok(Favorites {
+                version: FAVORITES_VERSION,
+                owner: v1.owner,
+                number: v1.number,
+                color: v1.color,
+                authority: v1.authority,
+                pending_authority: v1.pending_authority,
+                authority_can_close: v1.authority_can_close,
+                entries: Vec::new(),
+                delegates: Vec::new(),
+                history_enabled: false,
+            })
+        }
+        Some(2) => {
+            let v2 = FavoritesV2::deserialize(&mut body)?;
+            Ok(Favorites {
+                version: FAVORITES_VERSION,
+                owner: v2.owner,
+                number: v2.number,
+                color: v2.color,
+                authority: v2.authority,
+                pending_authority: v2.pending_authority,
+                authority_can_close: v2.authority_can_close,
+                entries: v2.entries,
+                delegates: Vec::new(),
+                history_enabled: false,
+            })
+        }
+        Some(3) => {
+            let v3 = FavoritesV3::deserialize(&mut body)?;
+            Ok(Favorites {
+                version: FAVORITES_VERSION,
+                owner: v3.owner,
+                number: v3.number,
+                color: v3.color,
+                authority: v3.authority,
+                pending_authority: v3.pending_authority,
+                authority_can_close: v3.authority_can_close,
+                entries: v3.entries,
+                delegates: v3.delegates,
+                history_enabled: false,
+            })
+        }
+        _ => err!(ErrorCode::AlreadyMigrated),
+    }
+}
+
+pub fn migrate_favorites<'info>(
+    favorites: &UncheckedAccount<'info>,
+    owner: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let migrated = read_outdated_favorites(&favorites.try_borrow_data()?, owner.key())?;
+
+    // The owner pays for any extra space.
+    let new_size = Favorites::space(migrated.entries.len());
+    let rent_difference = Rent::get()?
+        .minimum_balance(new_size)
+        .saturating_sub(favorites.lamports());
+    if rent_difference > 0 {
+        let cpi_context = CpiContext::new(
+            system_program.to_account_info(),
+            Transfer {
+                from: owner.to_account_info(),
+                to: favorites.to_account_info(),
+            },
+        );
+        transfer(cpi_context, rent_difference)?;
+    }
+    favorites.resize(new_size)?;
+
+    let mut data = favorites.try_borrow_mut_data()?;
+    migrated.try_serialize(&mut &mut data[..])
+}
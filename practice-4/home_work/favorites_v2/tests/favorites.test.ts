@@ -2,13 +2,47 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program, web3 } from "@coral-xyz/anchor";
 import { Favorites } from "../target/types/favorites";
 import { airdropIfRequired, getCustomErrorMessage } from "@solana-developers/helpers";
-import { expect, describe, test } from '@jest/globals';
+import { expect, describe, test, beforeAll } from '@jest/globals';
 import { systemProgramErrors } from "./system-program-errors";
+import { createHash } from "crypto";
+import { readFileSync } from "fs";
 
 describe("favorites", () => {
   // Configure the client to use the local cluster.
   anchor.setProvider(anchor.AnchorProvider.env());
 
+  const program = anchor.workspace.Favorites as Program<Favorites>;
+
+  const favoritesPdaFor = (owner: web3.Keypair) =>
+    web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("favorites"), owner.publicKey.toBuffer()],
+      program.programId
+    )[0];
+
+  const fundSigners = async (...signers: web3.Keypair[]) => {
+    for (const keypair of signers) {
+      await airdropIfRequired(
+        anchor.getProvider().connection,
+        keypair.publicKey,
+        0.5 * web3.LAMPORTS_PER_SOL,
+        web3.LAMPORTS_PER_SOL
+      );
+    }
+  };
+
+  // Funds every signer, then has `owner` create favorites of 23 and "red".
+  const setupFavorites = async (owner: web3.Keypair, ...extraSigners: web3.Keypair[]) => {
+    await fundSigners(owner, ...extraSigners);
+    await program.methods
+      .upsertFavorites(new anchor.BN(23), "red", null, false)
+      .accounts({
+        user: owner.publicKey,
+        owner: owner.publicKey,
+      })
+      .signers([owner])
+      .rpc();
+  };
+
   it("Writes our favorites to the blockchain", async () => {
     const user = web3.Keypair.generate();
     const program = anchor.workspace.Favorites as Program<Favorites>;
@@ -27,10 +61,11 @@ describe("favorites", () => {
     let tx: string | null = null;
     try {
       tx = await program.methods
-        // Call the set_favorites instruction handler
-        .setFavorites(favoriteNumber, favoriteColor)
+        // Call the upsert_favorites instruction handler
+        .upsertFavorites(favoriteNumber, favoriteColor, null, false)
         .accounts({
           user: user.publicKey,
+          owner: user.publicKey,
           // Note that both `favorites` and `system_program` are added automatically.
         })
         // Sign the transaction
@@ -72,9 +107,10 @@ describe("favorites", () => {
     let tx: string | null = null;
     try {
       tx = await program.methods
-        .setFavorites(initialNumber, initialColor)
+        .upsertFavorites(initialNumber, initialColor, null, false)
         .accounts({
           user: user.publicKey,
+          owner: user.publicKey,
         })
         .signers([user])
         .rpc();
@@ -97,7 +133,7 @@ describe("favorites", () => {
 
     try {
       tx = await program.methods
-        .updateFavorites(newNumber, newColor)
+        .updateFavorites(newNumber, newColor, false)
         .accounts({
           user: user.publicKey,
           owner: user.publicKey,
@@ -131,9 +167,10 @@ describe("favorites", () => {
     let tx: string | null = null;
     try {
       tx = await program.methods
-        .setFavorites(initialNumber, initialColor)
+        .upsertFavorites(initialNumber, initialColor, null, false)
         .accounts({
           user: user.publicKey,
+          owner: user.publicKey,
         })
         .signers([user])
         .rpc();
@@ -155,7 +192,7 @@ describe("favorites", () => {
     
     try {
       tx = await program.methods
-        .updateFavorites(newNumber, null)
+        .updateFavorites(newNumber, null, false)
         .accounts({
           user: user.publicKey,
           owner: user.publicKey,
@@ -198,9 +235,10 @@ describe("favorites", () => {
     let tx: string | null = null;
     try {
       tx = await program.methods
-        .setFavoritesWithAuthority(initialNumber, initialColor, delegate.publicKey)
+        .upsertFavorites(initialNumber, initialColor, delegate.publicKey, false)
         .accounts({
           user: owner.publicKey,
+          owner: owner.publicKey,
         })
         .signers([owner])
         .rpc();
@@ -217,6 +255,20 @@ describe("favorites", () => {
     let dataFromPda = await program.account.favorites.fetch(favoritesPda);
     expect(dataFromPda.color).toEqual(initialColor);
     expect(dataFromPda.number.toNumber()).toEqual(initialNumber.toNumber());
+    expect(dataFromPda.authority).toBeNull();
+    expect(dataFromPda.pendingAuthority?.toBase58()).toEqual(delegate.publicKey.toBase58());
+
+    // The delegate only takes over once it accepts
+    await program.methods
+      .acceptAuthority()
+      .accounts({
+        pendingAuthority: delegate.publicKey,
+        owner: owner.publicKey,
+      })
+      .signers([delegate])
+      .rpc();
+
+    dataFromPda = await program.account.favorites.fetch(favoritesPda);
     expect(dataFromPda.authority?.toBase58()).toEqual(delegate.publicKey.toBase58());
 
     // Update through delegate
@@ -225,7 +277,7 @@ describe("favorites", () => {
 
     try {
       tx = await program.methods
-        .updateFavorites(newNumber, newColor)
+        .updateFavorites(newNumber, newColor, false)
         .accounts({
           user: delegate.publicKey,
           owner: owner.publicKey,
@@ -241,4 +293,1015 @@ describe("favorites", () => {
     expect(dataFromPda.color).toEqual(newColor);
     expect(dataFromPda.number.toNumber()).toEqual(newNumber.toNumber());
   });
+
+  it("Stores the owner on the favorites account", async () => {
+    const user = web3.Keypair.generate();
+    await setupFavorites(user);
+
+    const dataFromPda = await program.account.favorites.fetch(favoritesPdaFor(user));
+    expect(dataFromPda.owner.toBase58()).toEqual(user.publicKey.toBase58());
+  });
+
+  describe("set_authority", () => {
+    const owner = web3.Keypair.generate();
+    const authority = web3.Keypair.generate();
+    const stranger = web3.Keypair.generate();
+
+    const favoritesPda = favoritesPdaFor(owner);
+
+    const setAuthority = (signer: web3.Keypair, newAuthority: web3.PublicKey | null) =>
+      program.methods
+        .setAuthority(newAuthority)
+        .accounts({
+          user: signer.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    beforeAll(async () => {
+      await setupFavorites(owner, authority, stranger);
+    });
+
+    it("Refuses to hand the authority over in one step", async () => {
+      await expect(setAuthority(owner, authority.publicKey)).rejects.toThrow(
+        /AuthorityNeedsAcceptance/
+      );
+
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.authority).toBeNull();
+    });
+
+    it("Lets the current authority revoke itself", async () => {
+      await program.methods
+        .proposeAuthority(authority.publicKey)
+        .accounts({
+          user: owner.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+      await program.methods
+        .acceptAuthority()
+        .accounts({
+          pendingAuthority: authority.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      await expect(setAuthority(authority, stranger.publicKey)).rejects.toThrow(
+        /AuthorityNeedsAcceptance/
+      );
+      await setAuthority(authority, null);
+
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.authority).toBeNull();
+
+      // The previous authority has lost its rights.
+      await expect(setAuthority(authority, null)).rejects.toThrow(/Unauthorized/);
+    });
+
+    it("Rejects a stranger", async () => {
+      await expect(setAuthority(stranger, null)).rejects.toThrow(/Unauthorized/);
+    });
+  });
+
+  describe("two-step authority handover", () => {
+    const owner = web3.Keypair.generate();
+    const candidate = web3.Keypair.generate();
+    const stranger = web3.Keypair.generate();
+
+    const favoritesPda = favoritesPdaFor(owner);
+
+    const proposeAuthority = (signer: web3.Keypair, newAuthority: web3.PublicKey) =>
+      program.methods
+        .proposeAuthority(newAuthority)
+        .accounts({
+          user: signer.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    const acceptAuthority = (signer: web3.Keypair) =>
+      program.methods
+        .acceptAuthority()
+        .accounts({
+          pendingAuthority: signer.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    const cancelAuthorityTransfer = (signer: web3.Keypair) =>
+      program.methods
+        .cancelAuthorityTransfer()
+        .accounts({
+          user: signer.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    beforeAll(async () => {
+      await setupFavorites(owner, candidate, stranger);
+    });
+
+    it("Keeps the authority until the candidate accepts", async () => {
+      await proposeAuthority(owner, candidate.publicKey);
+
+      let dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.authority).toBeNull();
+      expect(dataFromPda.pendingAuthority?.toBase58()).toEqual(candidate.publicKey.toBase58());
+
+      await expect(acceptAuthority(stranger)).rejects.toThrow(/NotPendingAuthority/);
+
+      await acceptAuthority(candidate);
+
+      dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.authority?.toBase58()).toEqual(candidate.publicKey.toBase58());
+      expect(dataFromPda.pendingAuthority).toBeNull();
+    });
+
+    it("Lets the owner cancel a pending transfer", async () => {
+      await proposeAuthority(owner, stranger.publicKey);
+      await expect(cancelAuthorityTransfer(stranger)).rejects.toThrow(/Unauthorized/);
+
+      await cancelAuthorityTransfer(owner);
+
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.pendingAuthority).toBeNull();
+      await expect(acceptAuthority(stranger)).rejects.toThrow(/NotPendingAuthority/);
+      await expect(cancelAuthorityTransfer(owner)).rejects.toThrow(/NoPendingAuthority/);
+    });
+
+    it("Emits an event when a transfer is proposed", async () => {
+      let listener: number | null = null;
+      const event = new Promise<any>((resolve) => {
+        listener = program.addEventListener("authorityTransferProposed", resolve);
+      });
+
+      await proposeAuthority(candidate, stranger.publicKey);
+
+      const proposed = await event;
+      await program.removeEventListener(listener!);
+      expect(proposed.favorites.toBase58()).toEqual(favoritesPda.toBase58());
+      expect(proposed.proposedBy.toBase58()).toEqual(candidate.publicKey.toBase58());
+      expect(proposed.pendingAuthority.toBase58()).toEqual(stranger.publicKey.toBase58());
+    });
+  });
+
+  describe("close_favorites", () => {
+    const connection = anchor.getProvider().connection;
+    const owner = web3.Keypair.generate();
+    const authority = web3.Keypair.generate();
+    const recipient = web3.Keypair.generate();
+
+    const favoritesPda = favoritesPdaFor(owner);
+
+    const closeFavorites = (signer: web3.Keypair) =>
+      program.methods
+        .closeFavorites()
+        .accounts({
+          user: signer.publicKey,
+          owner: owner.publicKey,
+          recipient: recipient.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    beforeAll(async () => {
+      await fundSigners(owner, authority);
+    });
+
+    it("Only lets the authority close once the owner allows it", async () => {
+      await program.methods
+        .upsertFavorites(new anchor.BN(23), "red", authority.publicKey, false)
+        .accounts({
+          user: owner.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+      await program.methods
+        .setAuthorityCanClose(true)
+        .accounts({
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+
+      // A proposed authority that hasn't accepted yet can't close.
+      await expect(closeFavorites(authority)).rejects.toThrow(/Unauthorized/);
+
+      await program.methods
+        .acceptAuthority()
+        .accounts({
+          pendingAuthority: authority.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+      await program.methods
+        .setAuthorityCanClose(false)
+        .accounts({
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+
+      await expect(closeFavorites(authority)).rejects.toThrow(/Unauthorized/);
+
+      await program.methods
+        .setAuthorityCanClose(true)
+        .accounts({
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+
+      const rent = await connection.getBalance(favoritesPda);
+      await closeFavorites(authority);
+
+      expect(await connection.getAccountInfo(favoritesPda)).toBeNull();
+      expect(await connection.getBalance(recipient.publicKey)).toEqual(rent);
+    });
+
+    it("Lets the owner close and set favorites again", async () => {
+      await program.methods
+        .upsertFavorites(new anchor.BN(7), "green", null, false)
+        .accounts({
+          user: owner.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+
+      await closeFavorites(owner);
+      expect(await connection.getAccountInfo(favoritesPda)).toBeNull();
+
+      await program.methods
+        .upsertFavorites(new anchor.BN(8), "blue", null, false)
+        .accounts({
+          user: owner.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.number.toNumber()).toEqual(8);
+      expect(dataFromPda.color).toEqual("blue");
+    });
+  });
+
+  describe("upsert_favorites", () => {
+    const owner = web3.Keypair.generate();
+    const authority = web3.Keypair.generate();
+    const stranger = web3.Keypair.generate();
+
+    const favoritesPda = favoritesPdaFor(owner);
+
+    const upsertFavorites = (
+      signer: web3.Keypair,
+      number: number,
+      color: string,
+      newAuthority: web3.PublicKey | null
+    ) =>
+      program.methods
+        .upsertFavorites(new anchor.BN(number), color, newAuthority, false)
+        .accounts({
+          user: signer.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    beforeAll(async () => {
+      await fundSigners(owner, authority, stranger);
+    });
+
+    const acceptAuthority = (signer: web3.Keypair) =>
+      program.methods
+        .acceptAuthority()
+        .accounts({
+          pendingAuthority: signer.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    it("Overwrites existing favorites and keeps the authority", async () => {
+      await upsertFavorites(owner, 23, "red", authority.publicKey);
+      await acceptAuthority(authority);
+      await upsertFavorites(owner, 42, "blue", null);
+
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.number.toNumber()).toEqual(42);
+      expect(dataFromPda.color).toEqual("blue");
+      expect(dataFromPda.authority?.toBase58()).toEqual(authority.publicKey.toBase58());
+    });
+
+    it("Only proposes a new authority, which takes over once it accepts", async () => {
+      await upsertFavorites(owner, 42, "blue", stranger.publicKey);
+
+      let dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.authority?.toBase58()).toEqual(authority.publicKey.toBase58());
+      expect(dataFromPda.pendingAuthority?.toBase58()).toEqual(stranger.publicKey.toBase58());
+
+      await acceptAuthority(stranger);
+
+      dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.authority?.toBase58()).toEqual(stranger.publicKey.toBase58());
+      expect(dataFromPda.pendingAuthority).toBeNull();
+    });
+
+    it("Rejects an overwrite by anyone but the owner", async () => {
+      await expect(upsertFavorites(authority, 1, "black", null)).rejects.toThrow(/NotOwner/);
+      await expect(upsertFavorites(stranger, 1, "black", stranger.publicKey)).rejects.toThrow(
+        /NotOwner/
+      );
+    });
+  });
+
+  describe("color validation", () => {
+    const user = web3.Keypair.generate();
+
+    const favoritesPda = favoritesPdaFor(user);
+
+    const upsertColor = (color: string, normalize = false) =>
+      program.methods
+        .upsertFavorites(new anchor.BN(23), color, null, normalize)
+        .accounts({
+          user: user.publicKey,
+          owner: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+    const updateColor = (color: string, normalize = false) =>
+      program.methods
+        .updateFavorites(null, color, normalize)
+        .accounts({
+          user: user.publicKey,
+          owner: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+    beforeAll(async () => {
+      await fundSigners(user);
+    });
+
+    it("Accepts a color of exactly 50 bytes", async () => {
+      const color = "a".repeat(50);
+      await upsertColor(color);
+
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.color).toEqual(color);
+    });
+
+    it("Rejects a color of 51 bytes", async () => {
+      await expect(upsertColor("a".repeat(51))).rejects.toThrow(/ColorTooLong/);
+      await expect(updateColor("a".repeat(51))).rejects.toThrow(/ColorTooLong/);
+    });
+
+    it("Counts multibyte characters by their UTF-8 length", async () => {
+      // "ж" is 2 bytes in UTF-8: 25 of them fit, 26 don't even though it's only 26 characters.
+      const fits = "ж".repeat(25);
+      expect(Buffer.byteLength(fits, "utf8")).toEqual(50);
+      await updateColor(fits);
+
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.color).toEqual(fits);
+
+      await expect(updateColor("ж".repeat(26))).rejects.toThrow(/ColorTooLong/);
+      // 16 four-byte emoji plus two ASCII characters is 66 bytes.
+      await expect(updateColor("🎨".repeat(16) + "ok")).rejects.toThrow(/ColorTooLong/);
+    });
+
+    it("Stores the color as given unless asked to normalize", async () => {
+      await updateColor(" Sky Blue ");
+
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.color).toEqual(" Sky Blue ");
+    });
+
+    it("Trims and lower-cases known color names", async () => {
+      await updateColor("  NAVY ", true);
+
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.color).toEqual("navy");
+    });
+
+    it("Rejects unknown color names when normalizing", async () => {
+      await expect(updateColor("Sky Blue", true)).rejects.toThrow(/UnknownColorName/);
+      await expect(upsertColor("redd", true)).rejects.toThrow(/UnknownColorName/);
+      await expect(updateColor("b".repeat(50), true)).rejects.toThrow(/UnknownColorName/);
+
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.color).toEqual("navy");
+    });
+
+    it("Normalizes hex colors", async () => {
+      await updateColor("#FFF", true);
+      let dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.color).toEqual("#ffffff");
+
+      await upsertColor(" #00AaFf ", true);
+      dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.color).toEqual("#00aaff");
+
+      await expect(updateColor("#12345", true)).rejects.toThrow(/InvalidColor/);
+      await expect(updateColor("#gggggg", true)).rejects.toThrow(/InvalidColor/);
+      await expect(updateColor("   ", true)).rejects.toThrow(/InvalidColor/);
+    });
+  });
+
+  describe("migrate_favorites", () => {
+    const user = web3.Keypair.generate();
+
+    const favoritesPda = favoritesPdaFor(user);
+
+    beforeAll(async () => {
+      await setupFavorites(user);
+    });
+
+    it("Creates new accounts with the current version", async () => {
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.version).toEqual(4);
+    });
+
+    it("Refuses to migrate an account that is already current", async () => {
+      await expect(
+        program.methods
+          .migrateFavorites()
+          .accounts({
+            owner: user.publicKey,
+          })
+          .signers([user])
+          .rpc()
+      ).rejects.toThrow(/AlreadyMigrated/);
+    });
+
+    // Owners of the unversioned accounts preloaded from tests/fixtures, see Anchor.toml.
+    const fixtureOwner = (name: string) =>
+      web3.Keypair.fromSecretKey(
+        Uint8Array.from(
+          JSON.parse(readFileSync(`${__dirname}/fixtures/${name}-owner.json`, "utf8"))
+        )
+      );
+
+    const migrateFavorites = (owner: web3.Keypair) =>
+      program.methods
+        .migrateFavorites()
+        .accounts({
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+
+    it("Migrates an account from before favorites were versioned", async () => {
+      const owner = fixtureOwner("unversioned-23");
+      await fundSigners(owner);
+      const pda = favoritesPdaFor(owner);
+
+      // 23 as the first byte is not a known version, the size gives it away.
+      await migrateFavorites(owner);
+
+      const dataFromPda = await program.account.favorites.fetch(pda);
+      expect(dataFromPda.version).toEqual(4);
+      expect(dataFromPda.owner.toBase58()).toEqual(owner.publicKey.toBase58());
+      expect(dataFromPda.number.toNumber()).toEqual(23);
+      expect(dataFromPda.color).toEqual("red");
+      expect(dataFromPda.authority).toBeNull();
+      expect(dataFromPda.entries).toEqual([]);
+
+      await expect(migrateFavorites(owner)).rejects.toThrow(/AlreadyMigrated/);
+
+      // The migrated account works like any other.
+      await program.methods
+        .updateFavorites(new anchor.BN(42), null, false)
+        .accounts({
+          user: owner.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+      expect((await program.account.favorites.fetch(pda)).number.toNumber()).toEqual(42);
+    });
+
+    it("Doesn't mistake a number's low byte for a version", async () => {
+      // 258 is 0x0102: its first byte reads as version 2.
+      const owner = fixtureOwner("unversioned-258");
+      await fundSigners(owner);
+
+      await migrateFavorites(owner);
+
+      const dataFromPda = await program.account.favorites.fetch(favoritesPdaFor(owner));
+      expect(dataFromPda.owner.toBase58()).toEqual(owner.publicKey.toBase58());
+      expect(dataFromPda.number.toNumber()).toEqual(258);
+      expect(dataFromPda.color).toEqual("blue");
+      expect(dataFromPda.authority?.toBase58()).toEqual(
+        fixtureOwner("unversioned-23").publicKey.toBase58()
+      );
+    });
+  });
+
+  describe("import_legacy_favorites", () => {
+    const connection = anchor.getProvider().connection;
+    const user = web3.Keypair.generate();
+    const legacyProgramId = new web3.PublicKey("329AjHWJsuehP7qqhGrte4FSHng5hEkyife64j2haZnj");
+
+    const favoritesPda = favoritesPdaFor(user);
+    const [legacyPda, _legacyBump] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("favorites"), user.publicKey.toBuffer()],
+      legacyProgramId
+    );
+
+    const importLegacyFavorites = () =>
+      program.methods
+        .importLegacyFavorites()
+        .accounts({
+          owner: user.publicKey,
+          legacyFavorites: legacyPda,
+        } as any)
+        .signers([user])
+        .rpc();
+
+    beforeAll(async () => {
+      await fundSigners(user);
+
+      // Calls `set_favorites(23, "purple")` on the practice-3 program by hand,
+      // as there is no client for it in this workspace.
+      const color = Buffer.from("purple");
+      const colorLength = Buffer.alloc(4);
+      colorLength.writeUInt32LE(color.length);
+      const setFavoritesIx = new web3.TransactionInstruction({
+        programId: legacyProgramId,
+        keys: [
+          { pubkey: user.publicKey, isSigner: true, isWritable: true },
+          { pubkey: legacyPda, isSigner: false, isWritable: true },
+          { pubkey: web3.SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.concat([
+          createHash("sha256").update("global:set_favorites").digest().subarray(0, 8),
+          new anchor.BN(23).toArrayLike(Buffer, "le", 8),
+          colorLength,
+          color,
+        ]),
+      });
+      await web3.sendAndConfirmTransaction(
+        connection,
+        new web3.Transaction().add(setFavoritesIx),
+        [user]
+      );
+    });
+
+    it("Copies a practice-3 account into the current layout", async () => {
+      expect((await connection.getAccountInfo(legacyPda))!.owner.toBase58()).toEqual(
+        legacyProgramId.toBase58()
+      );
+
+      await importLegacyFavorites();
+
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.version).toEqual(4);
+      expect(dataFromPda.owner.toBase58()).toEqual(user.publicKey.toBase58());
+      expect(dataFromPda.number.toNumber()).toEqual(23);
+      expect(dataFromPda.color).toEqual("purple");
+      expect(dataFromPda.authority).toBeNull();
+      expect(dataFromPda.entries).toEqual([]);
+
+      // The imported account works like any other.
+      await program.methods
+        .updateFavorites(new anchor.BN(42), null, false)
+        .accounts({
+          user: user.publicKey,
+          owner: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+      expect((await program.account.favorites.fetch(favoritesPda)).number.toNumber()).toEqual(42);
+    });
+
+    it("Refuses to import twice", async () => {
+      await expect(importLegacyFavorites()).rejects.toThrow(/already in use/);
+    });
+
+    it("Refuses an owner without a practice-3 account", async () => {
+      const stranger = web3.Keypair.generate();
+      await fundSigners(stranger);
+
+      await expect(
+        program.methods
+          .importLegacyFavorites()
+          .accounts({
+            owner: stranger.publicKey,
+            legacyFavorites: web3.PublicKey.findProgramAddressSync(
+              [Buffer.from("favorites"), stranger.publicKey.toBuffer()],
+              legacyProgramId
+            )[0],
+          } as any)
+          .signers([stranger])
+          .rpc()
+      ).rejects.toThrow(/ConstraintOwner/);
+    });
+  });
+
+  describe("key-value entries", () => {
+    const connection = anchor.getProvider().connection;
+    const user = web3.Keypair.generate();
+
+    const favoritesPda = favoritesPdaFor(user);
+
+    const setEntry = (key: string, value: string) =>
+      program.methods
+        .setEntry(key, value)
+        .accounts({
+          user: user.publicKey,
+          owner: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+    const removeEntry = (key: string) =>
+      program.methods
+        .removeEntry(key)
+        .accounts({
+          user: user.publicKey,
+          owner: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+    beforeAll(async () => {
+      await setupFavorites(user);
+    });
+
+    it("Grows the account for a new entry and keeps the legacy fields", async () => {
+      const sizeBefore = (await connection.getAccountInfo(favoritesPda))!.data.length;
+      const rentBefore = await connection.getBalance(favoritesPda);
+
+      await setEntry("food", "pizza");
+
+      const accountInfo = (await connection.getAccountInfo(favoritesPda))!;
+      expect(accountInfo.data.length).toBeGreaterThan(sizeBefore);
+      expect(accountInfo.lamports).toBeGreaterThan(rentBefore);
+
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.entries).toEqual([{ key: "food", value: "pizza" }]);
+      expect(dataFromPda.number.toNumber()).toEqual(23);
+      expect(dataFromPda.color).toEqual("red");
+    });
+
+    it("Overwrites an existing entry without growing", async () => {
+      const sizeBefore = (await connection.getAccountInfo(favoritesPda))!.data.length;
+
+      await setEntry("food", "sushi");
+
+      expect((await connection.getAccountInfo(favoritesPda))!.data.length).toEqual(sizeBefore);
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.entries).toEqual([{ key: "food", value: "sushi" }]);
+    });
+
+    it("Keeps update_favorites working next to entries", async () => {
+      await program.methods
+        .updateFavorites(new anchor.BN(42), null, false)
+        .accounts({
+          user: user.publicKey,
+          owner: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.number.toNumber()).toEqual(42);
+      expect(dataFromPda.entries).toHaveLength(1);
+    });
+
+    it("Shrinks the account and refunds rent when an entry is removed", async () => {
+      await setEntry("movie", "Dune");
+      const rentBefore = await connection.getBalance(favoritesPda);
+
+      await removeEntry("food");
+
+      expect(await connection.getBalance(favoritesPda)).toBeLessThan(rentBefore);
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.entries).toEqual([{ key: "movie", value: "Dune" }]);
+
+      await expect(removeEntry("food")).rejects.toThrow(/EntryNotFound/);
+    });
+
+    it("Rejects keys and values over their limits", async () => {
+      await expect(setEntry("k".repeat(33), "v")).rejects.toThrow(/EntryKeyTooLong/);
+      await expect(setEntry("", "v")).rejects.toThrow(/EntryKeyTooLong/);
+      await expect(setEntry("key", "v".repeat(65))).rejects.toThrow(/EntryValueTooLong/);
+    });
+  });
+
+  describe("delegates", () => {
+    const owner = web3.Keypair.generate();
+    const colorApp = web3.Keypair.generate();
+
+    // Mirrors the PERMISSION_* constants in the program.
+    const PERMISSION_NUMBER = 1;
+    const PERMISSION_COLOR = 2;
+    const PERMISSION_ENTRIES = 4;
+
+    const favoritesPda = favoritesPdaFor(owner);
+
+    const updateAs = (signer: web3.Keypair, number: number | null, color: string | null) =>
+      program.methods
+        .updateFavorites(number === null ? null : new anchor.BN(number), color, false)
+        .accounts({
+          user: signer.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    const addDelegate = (delegate: web3.PublicKey, permissions: number) =>
+      program.methods
+        .addDelegate(delegate, permissions)
+        .accounts({
+          user: owner.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+
+    beforeAll(async () => {
+      await setupFavorites(owner, colorApp);
+    });
+
+    it("Lets a color delegate change the color but not the number", async () => {
+      await addDelegate(colorApp.publicKey, PERMISSION_COLOR);
+
+      await updateAs(colorApp, null, "green");
+      const dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.color).toEqual("green");
+
+      await expect(updateAs(colorApp, 42, null)).rejects.toThrow(/Unauthorized/);
+      await expect(updateAs(colorApp, 42, "blue")).rejects.toThrow(/Unauthorized/);
+      await expect(
+        program.methods
+          .setEntry("food", "pizza")
+          .accounts({
+            user: colorApp.publicKey,
+            owner: owner.publicKey,
+          })
+          .signers([colorApp])
+          .rpc()
+      ).rejects.toThrow(/Unauthorized/);
+    });
+
+    it("Applies widened permissions and revocation", async () => {
+      await addDelegate(colorApp.publicKey, PERMISSION_COLOR | PERMISSION_NUMBER | PERMISSION_ENTRIES);
+      await updateAs(colorApp, 42, "blue");
+
+      let dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.number.toNumber()).toEqual(42);
+      expect(dataFromPda.delegates).toHaveLength(1);
+
+      await program.methods
+        .revokeDelegate(colorApp.publicKey)
+        .accounts({
+          user: owner.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+
+      dataFromPda = await program.account.favorites.fetch(favoritesPda);
+      expect(dataFromPda.delegates).toHaveLength(0);
+      await expect(updateAs(colorApp, null, "red")).rejects.toThrow(/Unauthorized/);
+    });
+
+    it("Rejects unknown permission bits", async () => {
+      await expect(addDelegate(colorApp.publicKey, 8)).rejects.toThrow(/InvalidPermissions/);
+      await expect(addDelegate(colorApp.publicKey, 0)).rejects.toThrow(/InvalidPermissions/);
+    });
+  });
+
+  describe("change events and history", () => {
+    const owner = web3.Keypair.generate();
+    const authority = web3.Keypair.generate();
+
+    const favoritesPda = favoritesPdaFor(owner);
+    const [historyPda, _historyBump] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("history"), favoritesPda.toBuffer()],
+      program.programId
+    );
+
+    // Resolves with the first `eventName` emitted while `action` runs.
+    const captureEvent = async (eventName: string, action: () => Promise<unknown>) => {
+      let listener: number | null = null;
+      const event = new Promise<any>((resolve) => {
+        listener = program.addEventListener(eventName as any, resolve);
+      });
+      await action();
+      const captured = await event;
+      await program.removeEventListener(listener!);
+      return captured;
+    };
+
+    const updateFavorites = (number: number | null, color: string | null) =>
+      program.methods
+        .updateFavorites(number === null ? null : new anchor.BN(number), color, false)
+        .accounts({
+          user: owner.publicKey,
+          owner: owner.publicKey,
+          history: historyPda,
+        } as any)
+        .signers([owner])
+        .rpc();
+
+    beforeAll(async () => {
+      await fundSigners(owner);
+    });
+
+    it("Emits FavoritesSet without old values on creation", async () => {
+      const event = await captureEvent("favoritesSet", () =>
+        program.methods
+          .upsertFavorites(new anchor.BN(23), "red", null, false)
+          .accounts({
+            user: owner.publicKey,
+            owner: owner.publicKey,
+          })
+          .signers([owner])
+          .rpc()
+      );
+
+      expect(event.favorites.toBase58()).toEqual(favoritesPda.toBase58());
+      expect(event.signer.toBase58()).toEqual(owner.publicKey.toBase58());
+      expect(event.oldNumber).toBeNull();
+      expect(event.oldColor).toBeNull();
+      expect(event.newNumber.toNumber()).toEqual(23);
+      expect(event.newColor).toEqual("red");
+    });
+
+    it("Emits FavoritesUpdated with old and new values", async () => {
+      await program.methods
+        .enableHistory()
+        .accounts({
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+
+      expect((await program.account.favorites.fetch(favoritesPda)).historyEnabled).toBe(true);
+
+      const event = await captureEvent("favoritesUpdated", () => updateFavorites(42, null));
+
+      expect(event.oldNumber.toNumber()).toEqual(23);
+      expect(event.newNumber.toNumber()).toEqual(42);
+      expect(event.oldColor).toEqual("red");
+      expect(event.newColor).toEqual("red");
+    });
+
+    it("Emits AuthorityChanged once a proposed authority accepts", async () => {
+      const proposed = await captureEvent("authorityTransferProposed", () =>
+        program.methods
+          .upsertFavorites(new anchor.BN(42), "red", authority.publicKey, false)
+          .accounts({
+            user: owner.publicKey,
+            owner: owner.publicKey,
+            history: historyPda,
+          } as any)
+          .signers([owner])
+          .rpc()
+      );
+      expect(proposed.proposedBy.toBase58()).toEqual(owner.publicKey.toBase58());
+      expect(proposed.pendingAuthority.toBase58()).toEqual(authority.publicKey.toBase58());
+
+      const event = await captureEvent("authorityChanged", () =>
+        program.methods
+          .acceptAuthority()
+          .accounts({
+            pendingAuthority: authority.publicKey,
+            owner: owner.publicKey,
+            history: historyPda,
+          } as any)
+          .signers([authority])
+          .rpc()
+      );
+
+      expect(event.signer.toBase58()).toEqual(authority.publicKey.toBase58());
+      expect(event.oldAuthority).toBeNull();
+      expect(event.newAuthority.toBase58()).toEqual(authority.publicKey.toBase58());
+
+      // Hand control back to the owner for the remaining tests.
+      await program.methods
+        .setAuthority(null)
+        .accounts({
+          user: owner.publicKey,
+          owner: owner.publicKey,
+          history: historyPda,
+        } as any)
+        .signers([owner])
+        .rpc();
+    });
+
+    it("Rejects changes without the history account once enabled", async () => {
+      await expect(
+        program.methods
+          .updateFavorites(new anchor.BN(1), null, false)
+          .accounts({
+            user: owner.publicKey,
+            owner: owner.publicKey,
+            history: null,
+          } as any)
+          .signers([owner])
+          .rpc()
+      ).rejects.toThrow(/HistoryAccountMissing/);
+    });
+
+    it("Keeps only the last changes in the history ring buffer", async () => {
+      let history = await program.account.favoritesHistory.fetch(historyPda);
+      // update_favorites, upsert_favorites, accept_authority and set_authority so far.
+      expect(history.records).toHaveLength(4);
+      expect(history.records[0].number.toNumber()).toEqual(42);
+      expect(history.records[1].authority).toBeNull();
+      expect(history.records[2].authority?.toBase58()).toEqual(authority.publicKey.toBase58());
+
+      for (let number = 0; number < 8; number++) {
+        await updateFavorites(number, null);
+      }
+
+      history = await program.account.favoritesHistory.fetch(historyPda);
+      expect(history.records).toHaveLength(8);
+      expect(history.nextIndex).toEqual(4);
+      // The oldest slot now holds the newest change.
+      expect(history.records[3].number.toNumber()).toEqual(7);
+      expect(history.records[4].number.toNumber()).toEqual(0);
+    });
+
+    it("Closes the history with the favorites, so it can be enabled again", async () => {
+      const connection = anchor.getProvider().connection;
+      const recipient = web3.Keypair.generate();
+
+      const closeFavorites = (history: web3.PublicKey | null) =>
+        program.methods
+          .closeFavorites()
+          .accounts({
+            user: owner.publicKey,
+            owner: owner.publicKey,
+            recipient: recipient.publicKey,
+            history,
+          } as any)
+          .signers([owner])
+          .rpc();
+
+      await expect(closeFavorites(null)).rejects.toThrow(/HistoryAccountMissing/);
+
+      const rent =
+        (await connection.getBalance(favoritesPda)) + (await connection.getBalance(historyPda));
+      await closeFavorites(historyPda);
+
+      expect(await connection.getAccountInfo(favoritesPda)).toBeNull();
+      expect(await connection.getAccountInfo(historyPda)).toBeNull();
+      expect(await connection.getBalance(recipient.publicKey)).toEqual(rent);
+
+      await program.methods
+        .upsertFavorites(new anchor.BN(23), "red", null, false)
+        .accounts({
+          user: owner.publicKey,
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+      expect((await program.account.favorites.fetch(favoritesPda)).historyEnabled).toBe(false);
+
+      await program.methods
+        .enableHistory()
+        .accounts({
+          owner: owner.publicKey,
+        })
+        .signers([owner])
+        .rpc();
+
+      const history = await program.account.favoritesHistory.fetch(historyPda);
+      expect(history.records).toHaveLength(0);
+    });
+  });
+
+  it("Returns favorites through the get_favorites view", async () => {
+    const user = web3.Keypair.generate();
+    await setupFavorites(user);
+
+    // Simulated, so it costs nothing and needs no signature.
+    const favorites = await program.methods
+      .getFavorites()
+      .accounts({
+        owner: user.publicKey,
+      })
+      .view();
+
+    expect(favorites.owner.toBase58()).toEqual(user.publicKey.toBase58());
+    expect(favorites.number.toNumber()).toEqual(23);
+    expect(favorites.color).toEqual("red");
+    expect(favorites.authority).toBeNull();
+  });
 });